@@ -0,0 +1,111 @@
+use std::fmt::Display;
+
+use sworker::POSITIONS_PER_GROUP;
+
+/// Modifiers and keys of the generated sway bindings.
+///
+/// Every modifier is added to `--modifier`, `none` adds nothing.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Bindings {
+    /// Modifier every binding starts with.
+    #[clap(long, default_value = "Mod4")]
+    modifier: String,
+    /// Modifier to focus a position.
+    #[clap(long, default_value = "none")]
+    focus_mod: String,
+    /// Modifier to move the focused container to a position.
+    #[clap(long, default_value = "Shift")]
+    move_mod: String,
+    /// Modifier to focus a group, and with the arrow keys the next or previous position and group.
+    #[clap(long, default_value = "Ctrl")]
+    focus_group_mod: String,
+    /// Modifier to move the focused container to a group, and with the arrow keys to the next or previous one.
+    #[clap(long, default_value = "Alt")]
+    move_group_mod: String,
+    /// Key inserting a new workspace after the focused one, combined with `--move-mod` to move there.
+    #[clap(long, default_value = "plus")]
+    insert_next: String,
+    /// Key inserting a new workspace before the focused one, combined with `--move-mod` to move there.
+    #[clap(long, default_value = "minus")]
+    insert_prev: String,
+}
+
+impl Bindings {
+    /// The sway config snippet binding every position, group, direction and insertion.
+    pub fn generate(&self) -> String {
+        let focus = self.modifiers(&self.focus_mod);
+        let moves = self.modifiers(&self.move_mod);
+        let focus_group = self.modifiers(&self.focus_group_mod);
+        let move_group = self.modifiers(&self.move_group_mod);
+
+        // Positions and groups are put on the number keys.
+        let positions = (1..=POSITIONS_PER_GROUP).collect::<Vec<_>>();
+        let groups = (1..=9).collect::<Vec<_>>();
+
+        let sections = [
+            ("Move focus between workspaces", numbered(&focus, "focus", &positions)),
+            ("Move containers between workspaces", numbered(&moves, "move", &positions)),
+            ("Move focus between outputs", numbered(&focus_group, "focus-group", &groups)),
+            ("Move containers between outputs", numbered(&move_group, "move-group", &groups)),
+            (
+                "Move focus to the next and previous workspace and output",
+                vec![
+                    bind(&focus_group, "Left", "focus prev"),
+                    bind(&focus_group, "Right", "focus next"),
+                    bind(&focus_group, "Up", "focus-group next"),
+                    bind(&focus_group, "Down", "focus-group prev"),
+                ],
+            ),
+            (
+                "Move containers to the next and previous workspace and output",
+                vec![
+                    bind(&move_group, "Left", "move prev"),
+                    bind(&move_group, "Right", "move next"),
+                    bind(&move_group, "Up", "move-group next"),
+                    bind(&move_group, "Down", "move-group prev"),
+                ],
+            ),
+            (
+                "Insert a new workspace after or before the focused one",
+                vec![
+                    bind(&focus, &self.insert_next, "focus --new next"),
+                    bind(&focus, &self.insert_prev, "focus --new prev"),
+                    bind(&moves, &self.insert_next, "move --new next"),
+                    bind(&moves, &self.insert_prev, "move --new prev"),
+                ],
+            ),
+        ];
+
+        let header = format!("# Generated by `{} generate-bindings`\n", env!("CARGO_PKG_NAME"));
+
+        sections.into_iter().fold(header, |config, (title, bindings)| {
+            format!("{config}\n# {title}\n{}\n", bindings.join("\n"))
+        })
+    }
+
+    /// `--modifier` followed by `modifier`, joined as sway expects them.
+    fn modifiers(&self, modifier: &str) -> String {
+        [self.modifier.as_str(), modifier]
+            .into_iter()
+            .filter(|modifier| !modifier.is_empty() && *modifier != "none")
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+/// Bind the number key of each of `numbers` to run `command` with it.
+fn numbered(modifiers: &str, command: &str, numbers: &[i32]) -> Vec<String> {
+    numbers
+        .iter()
+        .map(|number| bind(modifiers, number, format!("{command} {number}")))
+        .collect()
+}
+
+/// A binding of `key` to run `command`.
+fn bind(modifiers: &str, key: impl Display, command: impl Display) -> String {
+    if modifiers.is_empty() {
+        format!("bindsym {key} exec {} {command}", env!("CARGO_PKG_NAME"))
+    } else {
+        format!("bindsym {modifiers}+{key} exec {} {command}", env!("CARGO_PKG_NAME"))
+    }
+}
@@ -1,23 +1,50 @@
-use clap::builder::PossibleValue;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::Arg;
+
+use regex::Regex;
+use sworker::{Config, Criteria, Direction, OutputOrder, SortKey};
+
+use crate::bindings::Bindings;
 
 #[derive(Debug, Clone, clap::Parser)]
 #[clap(bin_name = env!("CARGO_PKG_NAME"), version = env!("CARGO_PKG_VERSION"), about = env!("CARGO_PKG_DESCRIPTION"))]
-pub enum Cli {
+pub struct Cli {
+    /// Order the outputs are given their groups in, overriding the config:
+    /// top-to-bottom, left-to-right, name, sway, focused or output names separated by commas.
+    #[clap(long, global = true)]
+    pub output_order: Option<OutputOrder>,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum Command {
     /// Start a daemon that reorders workspaces on workspace creation and output changes
     Daemon,
     /// Reorder all workspaces to their correct number/index
     Reorder,
     /// Change focus to the desired position of the focused group.
     Focus {
-        /// Direction or position to focus.
+        /// Direction or position to focus:
+        /// next, prev, back, urgent, empty, home, first, last, a number, an offset like +2 or group:position.
+        #[clap(value_parser = ActionParser::FOCUS, allow_negative_numbers = true, hide_possible_values = true)]
         action: Action,
         /// Insert a new workspace at the position instead of focusing the one already there.
         #[clap(short, long)]
         new: bool,
+        /// Groups `urgent` looks for urgent workspaces in.
+        #[clap(short, long, value_enum, default_value_t = Scope::Any)]
+        group: Scope,
     },
     /// Move the focused container to the desired position of the focused group.
     Move {
-        /// Direction or position to focus.
+        /// Direction or position to move to:
+        /// next, prev, back, empty, home, first, last, a number, an offset like +2 or group:position.
+        #[clap(value_parser = ActionParser::MOVE, allow_negative_numbers = true, hide_possible_values = true)]
         action: Action,
         /// Insert a new workspace at the position instead of moving to the one already there.
         #[clap(short, long)]
@@ -26,55 +53,406 @@ pub enum Cli {
     /// Change focus to the desired group.
     /// Position will be retained.
     FocusGroup {
-        /// Direction or position to focus.
+        /// Direction or group to focus:
+        /// next, prev, back, first, last, left, right, up, down, a number or an offset like +2.
+        #[clap(value_parser = ActionParser::GROUP, allow_negative_numbers = true, hide_possible_values = true)]
         action: Action,
     },
     /// Move the focused container to the desired group.
     /// Position will be retained.
     MoveGroup {
-        /// Direction or position to focus.
+        /// Direction or group to move to:
+        /// next, prev, back, first, last, left, right, up, down, a number or an offset like +2.
+        #[clap(value_parser = ActionParser::GROUP, allow_negative_numbers = true, hide_possible_values = true)]
         action: Action,
     },
+    /// Move every window of an application to the focused workspace.
+    Gather {
+        #[clap(flatten)]
+        application: Application,
+        /// Gather the windows on a new workspace inserted after the focused one instead.
+        #[clap(short, long)]
+        new: bool,
+    },
+    /// Fold workspaces holding a single window into their previous neighbour and close up the gaps.
+    Compact {
+        /// Group to compact instead of the focused one.
+        #[clap(short, long)]
+        group: Option<i32>,
+        /// Fold the workspaces with the fewest windows instead, until no more than this many are left.
+        #[clap(short, long)]
+        count: Option<usize>,
+        /// Print the merges instead of carrying them out.
+        #[clap(short, long)]
+        dry_run: bool,
+    },
+    /// Sort the workspaces of the focused group.
+    Sort {
+        /// What to sort by.
+        #[clap(short, long, value_enum, default_value_t = SortBy::Name)]
+        by: SortBy,
+        /// Group to sort instead of the focused one.
+        #[clap(short, long, conflicts_with = "all")]
+        group: Option<i32>,
+        /// Sort every group.
+        #[clap(short, long)]
+        all: bool,
+        /// Remember the resulting order, to be restored with `--by saved`.
+        #[clap(short, long)]
+        save: bool,
+    },
+    /// Move workspaces between the outputs so that each holds a single group again.
+    Rebalance,
+    /// Exchange the outputs whole groups are on.
+    Group {
+        #[clap(subcommand)]
+        command: GroupCommand,
+    },
+    /// Undo the most recent command that changed the workspaces, one further step back per call.
+    /// Requires a running daemon.
+    Undo,
+    /// Pin the focused workspace to its position, so inserting, moving or closing others never shifts it.
+    Pin,
+    /// Let the focused workspace shift with the others again.
+    Unpin,
+    /// Focus the workspaces from the most to the least recently focused, one step per call.
+    /// Requires a running daemon.
+    Cycle {
+        /// Go back towards the more recently focused workspaces instead.
+        #[clap(short, long)]
+        reverse: bool,
+    },
+    /// Print sway bindings for every position, group, direction and insertion, to be `include`d in the sway config.
+    GenerateBindings(Bindings),
+    /// Print the completions for `shell`.
+    Completions {
+        /// Shell to complete for.
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page.
+    Man {
+        /// Write a man page for every subcommand to this directory instead.
+        #[clap(short, long)]
+        dir: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Whether the command changes the workspaces or moves containers, so it can be undone.
+    pub const fn is_undoable(&self) -> bool {
+        matches!(
+            self,
+            Command::Focus { new: true, .. }
+                | Command::Move { .. }
+                | Command::MoveGroup { .. }
+                | Command::Gather { .. }
+                | Command::Compact { dry_run: false, .. }
+                | Command::Sort { .. }
+                | Command::Rebalance
+                | Command::Group { .. }
+        )
+    }
+
+    /// Fail if `config` does not allow the command, before it records anything to undo.
+    pub fn check(&self, config: &Config) -> Result<(), swayipc::Error> {
+        match self {
+            Command::Focus { action: Action::Home, .. } | Command::Move { action: Action::Home, .. } => {
+                config.check_home()
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum GroupCommand {
+    /// Move the workspaces of group `a` to the output of group `b` and the other way around.
+    /// Positions and focus will be retained.
+    Swap {
+        /// First group to swap.
+        a: i32,
+        /// Second group to swap.
+        b: i32,
+    },
+    /// Move every group to the output of the next group, the last one to the output of the first.
+    /// Positions and focus will be retained.
+    Rotate {
+        /// Move every group to the output of the previous group instead.
+        #[clap(short, long)]
+        reverse: bool,
+    },
+}
+
+/// The application whose windows are gathered.
+#[derive(Debug, Clone, clap::Args)]
+#[group(required = true, multiple = false)]
+pub struct Application {
+    /// Gather the windows whose Wayland application id matches this regular expression.
+    #[clap(long, value_parser = Regex::new)]
+    app_id: Option<Regex>,
+    /// Gather the X11 windows whose class matches this regular expression.
+    #[clap(long, value_parser = Regex::new)]
+    class: Option<Regex>,
+    /// Gather the windows of the application the focused window belongs to.
+    #[clap(long)]
+    focused_app: bool,
+}
+
+impl Application {
+    /// The criteria the windows have to match, none for the focused application.
+    pub fn criteria(self) -> Option<Criteria> {
+        (!self.focused_app).then_some(Criteria {
+            app_id: self.app_id,
+            class: self.class,
+            ..Criteria::default()
+        })
+    }
+}
+
+/// The shells completions can be generated for.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Elvish,
+    Fish,
+    Nushell,
+    Zsh,
+}
+
+/// What `sort` orders the workspaces by.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SortBy {
+    /// The name after the number, ignoring case.
+    Name,
+    /// The application of the first window, empty workspaces last.
+    App,
+    /// The number of windows, most first.
+    Windows,
+    /// When the workspace was created, oldest first.
+    Created,
+    /// When the workspace was focused last, most recent first. Requires a running daemon.
+    LastUsed,
+    /// The order remembered by `--save`.
+    Saved,
+    /// The order they have, to remember it with `--save` without sorting.
+    Current,
 }
 
-#[derive(Debug, Clone)]
+impl From<SortBy> for SortKey {
+    fn from(by: SortBy) -> Self {
+        match by {
+            SortBy::Name => SortKey::Name,
+            SortBy::App => SortKey::App,
+            SortBy::Windows => SortKey::Windows,
+            SortBy::Created => SortKey::Created,
+            SortBy::LastUsed => SortKey::LastUsed,
+            SortBy::Saved => SortKey::Saved,
+            SortBy::Current => SortKey::Current,
+        }
+    }
+}
+
+/// The groups a command looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Scope {
+    /// Only the focused group.
+    Current,
+    /// Every group, the focused one first.
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     Next,
     Prev,
+    /// The most recently focused workspace, as recorded by the daemon.
+    Back,
+    /// A workspace with an urgent window, the next one on every further call.
+    Urgent,
+    /// An empty workspace of the focused group, appended if there is none.
+    Empty,
+    /// The home workspace `x0` of the focused group.
+    Home,
+    First,
+    Last,
     Position(i32),
+    /// An offset from the focused position or group, written as `+2` or `-3`, wrapping at both ends.
+    Relative(i32),
+    /// A position of another group, written as `group:position`.
+    Address(i32, i32),
+    /// The group of the output next to the focused one on the screen.
+    Direction(Direction),
 }
 
-impl clap::ValueEnum for Action {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Action::Next,
-            Action::Prev,
-            Action::Position(1),
-            Action::Position(2),
-            Action::Position(3),
-            Action::Position(4),
-            Action::Position(5),
-            Action::Position(6),
-            Action::Position(7),
-            Action::Position(8),
-            Action::Position(9),
-        ]
+impl Action {
+    /// The named actions, as offered for completion.
+    const NAMED: [(&str, &str); 12] = [
+        ("next", "The next one, created at the end where the commands allow it"),
+        ("prev", "The previous one, created at the start where the commands allow it"),
+        ("back", "The most recently focused one, as recorded by the daemon"),
+        ("urgent", "A workspace with an urgent window"),
+        ("empty", "An empty workspace of the focused group"),
+        ("home", "The home workspace of the focused group"),
+        ("first", "The first one"),
+        ("last", "The last one"),
+        ("left", "The group of the output to the left"),
+        ("right", "The group of the output to the right"),
+        ("up", "The group of the output above"),
+        ("down", "The group of the output below"),
+    ];
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "next" => Ok(Action::Next),
+            "prev" => Ok(Action::Prev),
+            "back" => Ok(Action::Back),
+            "urgent" => Ok(Action::Urgent),
+            "empty" => Ok(Action::Empty),
+            "home" => Ok(Action::Home),
+            "first" => Ok(Action::First),
+            "last" => Ok(Action::Last),
+            "left" => Ok(Action::Direction(Direction::Left)),
+            "right" => Ok(Action::Direction(Direction::Right)),
+            "up" => Ok(Action::Direction(Direction::Up)),
+            "down" => Ok(Action::Direction(Direction::Down)),
+            _ if value.starts_with(['+', '-']) => match value.parse() {
+                Ok(offset) => Ok(Action::Relative(offset)),
+                Err(_) => Err(format!("'{value}' is not an offset like +2 or -3")),
+            },
+            _ => match value.split_once(':') {
+                Some((group, position)) => Ok(Action::Address(
+                    index(group).map_err(|err| format!("group of '{value}': {err}"))?,
+                    index(position).map_err(|err| format!("position of '{value}': {err}"))?,
+                )),
+                None if value.starts_with(|char: char| char.is_ascii_digit()) => Ok(Action::Position(index(value)?)),
+                None => Err(format!("'{value}' is not an action")),
+            },
+        }
     }
+}
 
-    fn to_possible_value(&self) -> Option<PossibleValue> {
-        match self {
-            Action::Next => Some(PossibleValue::new("next")),
-            Action::Prev => Some(PossibleValue::new("prev")),
-            Action::Position(1) => Some(PossibleValue::new("1")),
-            Action::Position(2) => Some(PossibleValue::new("2")),
-            Action::Position(3) => Some(PossibleValue::new("3")),
-            Action::Position(4) => Some(PossibleValue::new("4")),
-            Action::Position(5) => Some(PossibleValue::new("5")),
-            Action::Position(6) => Some(PossibleValue::new("6")),
-            Action::Position(7) => Some(PossibleValue::new("7")),
-            Action::Position(8) => Some(PossibleValue::new("8")),
-            Action::Position(9) => Some(PossibleValue::new("9")),
-            Action::Position(_) => None,
+/// Parse a group or position, both counted from 1.
+fn index(value: &str) -> Result<i32, String> {
+    if value.is_empty() || !value.chars().all(|char| char.is_ascii_digit()) {
+        return Err(format!("'{value}' is not a number"));
+    }
+
+    match value.parse::<i32>() {
+        Ok(index) if index >= 1 => Ok(index),
+        Ok(_) => Err(format!("'{value}' is out of range, counting starts at 1")),
+        Err(_) => Err(format!("'{value}' is out of range")),
+    }
+}
+
+/// Parses an [`Action`] a command takes, offering its named actions and the positions of a full group for
+/// completion.
+#[derive(Debug, Clone, Copy)]
+pub struct ActionParser {
+    /// The named actions the command takes.
+    named: &'static [&'static str],
+    /// Whether the command takes `group:position`.
+    address: bool,
+}
+
+impl ActionParser {
+    pub const FOCUS: Self = Self {
+        named: &["next", "prev", "back", "urgent", "empty", "home", "first", "last"],
+        address: true,
+    };
+
+    pub const MOVE: Self = Self {
+        named: &["next", "prev", "back", "empty", "home", "first", "last"],
+        address: true,
+    };
+
+    pub const GROUP: Self = Self {
+        named: &["next", "prev", "back", "first", "last", "left", "right", "up", "down"],
+        address: false,
+    };
+
+    /// Parse `value`, rejecting the actions the command does not take like any other invalid value.
+    fn parse(self, value: &str) -> Result<Action, String> {
+        let numeric = value.starts_with(|char: char| char.is_ascii_digit() || char == '+' || char == '-');
+
+        match value.parse::<Action>() {
+            Ok(Action::Address(..)) if !self.address => Err(self.expected()),
+            Ok(action @ (Action::Position(_) | Action::Relative(_) | Action::Address(..))) => Ok(action),
+            Ok(action) if self.named.contains(&value) => Ok(action),
+            Err(err) if numeric => Err(err),
+            Ok(_) | Err(_) => Err(self.expected()),
         }
     }
+
+    fn expected(self) -> String {
+        let address = if self.address { " or group:position" } else { "" };
+
+        format!("expected one of {}, a number, an offset like +2{address}", self.named.join(", "))
+    }
+}
+
+impl TypedValueParser for ActionParser {
+    type Value = Action;
+
+    fn parse_ref(&self, cmd: &clap::Command, arg: Option<&Arg>, value: &OsStr) -> Result<Self::Value, clap::Error> {
+        let parser = *self;
+
+        StringValueParser::new()
+            .try_map(move |value| parser.parse(&value))
+            .parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let named = Action::NAMED
+            .into_iter()
+            .filter(|(name, _)| self.named.contains(name))
+            .map(|(name, help)| PossibleValue::new(name).help(help));
+        let indices = ["1", "2", "3", "4", "5", "6", "7", "8", "9"].map(PossibleValue::new);
+
+        Some(Box::new(named.chain(indices)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_offsets_and_addresses() {
+        assert_eq!(ActionParser::FOCUS.parse("+2"), Ok(Action::Relative(2)));
+        assert_eq!(ActionParser::MOVE.parse("-3"), Ok(Action::Relative(-3)));
+        assert_eq!(ActionParser::GROUP.parse("+1"), Ok(Action::Relative(1)));
+        assert_eq!(ActionParser::FOCUS.parse("4"), Ok(Action::Position(4)));
+        assert_eq!(ActionParser::MOVE.parse("2:3"), Ok(Action::Address(2, 3)));
+        assert_eq!(ActionParser::GROUP.parse("left"), Ok(Action::Direction(Direction::Left)));
+    }
+
+    #[test]
+    fn rejects_numbers_out_of_range() {
+        assert_eq!(ActionParser::FOCUS.parse("0"), Err("'0' is out of range, counting starts at 1".to_owned()));
+        assert_eq!(ActionParser::FOCUS.parse("99999999999"), Err("'99999999999' is out of range".to_owned()));
+        assert_eq!(
+            ActionParser::FOCUS.parse("+99999999999"),
+            Err("'+99999999999' is not an offset like +2 or -3".to_owned())
+        );
+        assert_eq!(ActionParser::MOVE.parse("2:"), Err("position of '2:': '' is not a number".to_owned()));
+        assert_eq!(
+            ActionParser::MOVE.parse("0:1"),
+            Err("group of '0:1': '0' is out of range, counting starts at 1".to_owned())
+        );
+    }
+
+    #[test]
+    fn rejects_actions_the_command_does_not_take() {
+        assert_eq!(ActionParser::FOCUS.parse("urgent"), Ok(Action::Urgent));
+        assert_eq!(ActionParser::MOVE.parse("urgent"), Err(ActionParser::MOVE.expected()));
+        assert_eq!(ActionParser::GROUP.parse("2:3"), Err(ActionParser::GROUP.expected()));
+        assert_eq!(ActionParser::FOCUS.parse("left"), Err(ActionParser::FOCUS.expected()));
+        assert_eq!(ActionParser::GROUP.parse("home"), Err(ActionParser::GROUP.expected()));
+        assert_eq!(ActionParser::FOCUS.parse("sideways"), Err(ActionParser::FOCUS.expected()));
+    }
 }
@@ -0,0 +1,140 @@
+/// A workspace as far as compacting is concerned.
+pub(crate) struct Slot {
+    pub(crate) num: i32,
+    /// Number of windows, descending into split and floating containers.
+    pub(crate) windows: usize,
+    /// The containers directly on the workspace, which are moved as a whole to keep their layout.
+    pub(crate) containers: Vec<i64>,
+}
+
+/// A workspace folded into another one.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    pub from: i32,
+    pub to: i32,
+    /// Number of windows moved, including those earlier merges moved to `from`.
+    pub windows: usize,
+    pub(crate) containers: Vec<i64>,
+}
+
+/// A struct to plan which workspaces of a group are folded into which.
+///
+/// Every merge is planned on the workspaces as the merges before it left them, so the merges
+/// have to be carried out in order.
+pub(crate) struct Compactor(Vec<Slot>);
+
+impl Compactor {
+    /// Take the workspaces of a group, leaving out empty ones as there is nothing to move.
+    pub(crate) fn new(mut slots: Vec<Slot>) -> Self {
+        slots.retain(|slot| slot.windows > 0);
+        slots.sort_by_key(|slot| slot.num);
+
+        Self(slots)
+    }
+
+    /// Fold every workspace holding a single window into its previous neighbour, the first one into the next.
+    pub(crate) fn fold_single(mut self) -> Vec<Merge> {
+        let mut merges = Vec::new();
+        let mut index = 0;
+
+        while index < self.0.len() {
+            if self.0[index].windows == 1 && self.0.len() > 1 {
+                let target = if index > 0 { index - 1 } else { 1 };
+                merges.push(self.merge(index, target));
+            } else {
+                index += 1;
+            }
+        }
+
+        merges
+    }
+
+    /// Fold the workspace with the fewest windows into its smaller neighbour until at most `count` are left.
+    pub(crate) fn fold_to(mut self, count: usize) -> Vec<Merge> {
+        let mut merges = Vec::new();
+
+        while self.0.len() > count.max(1) {
+            let Some(index) = (0..self.0.len()).min_by_key(|index| self.0[*index].windows) else {
+                break;
+            };
+
+            let target = [index.checked_sub(1), Some(index + 1).filter(|next| *next < self.0.len())]
+                .into_iter()
+                .flatten()
+                .min_by_key(|neighbour| self.0[*neighbour].windows)
+                .unwrap_or(index);
+
+            merges.push(self.merge(index, target));
+        }
+
+        merges
+    }
+
+    /// Move everything of the workspace at `index` to the one at `target`.
+    fn merge(&mut self, index: usize, target: usize) -> Merge {
+        let from = self.0.remove(index);
+        let target = &mut self.0[if target > index { target - 1 } else { target }];
+
+        target.windows += from.windows;
+        target.containers.extend(&from.containers);
+
+        Merge {
+            from: from.num,
+            to: target.num,
+            windows: from.windows,
+            containers: from.containers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workspace holding `windows` in a single container numbered after it.
+    fn slot(num: i32, windows: usize) -> Slot {
+        Slot {
+            num,
+            windows,
+            containers: vec![i64::from(num)],
+        }
+    }
+
+    fn moves(merges: &[Merge]) -> Vec<(i32, i32, usize, Vec<i64>)> {
+        merges
+            .iter()
+            .map(|merge| (merge.from, merge.to, merge.windows, merge.containers.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn fold_single_folds_the_first_into_the_next() {
+        let slots = vec![slot(11, 1), slot(12, 3), slot(13, 1), slot(14, 0), slot(15, 1)];
+        let merges = Compactor::new(slots).fold_single();
+
+        assert_eq!(moves(&merges), [(11, 12, 1, vec![11]), (13, 12, 1, vec![13]), (15, 12, 1, vec![15])]);
+    }
+
+    #[test]
+    fn fold_single_leaves_a_single_workspace() {
+        assert!(Compactor::new(vec![slot(11, 1), slot(12, 0)]).fold_single().is_empty());
+    }
+
+    #[test]
+    fn fold_to_moves_what_earlier_merges_folded_in() {
+        let merges = Compactor::new(vec![slot(11, 1), slot(12, 1), slot(13, 5)]).fold_to(1);
+
+        assert_eq!(moves(&merges), [(11, 12, 1, vec![11]), (12, 13, 2, vec![12, 11])]);
+    }
+
+    #[test]
+    fn fold_to_counts() {
+        let slots = || vec![slot(11, 2), slot(12, 1), slot(13, 4)];
+
+        assert_eq!(moves(&Compactor::new(slots()).fold_to(0)), moves(&Compactor::new(slots()).fold_to(1)));
+        assert_eq!(moves(&Compactor::new(slots()).fold_to(1)), [(12, 11, 1, vec![12]), (11, 13, 3, vec![11, 12])]);
+        assert_eq!(moves(&Compactor::new(slots()).fold_to(2)), [(12, 11, 1, vec![12])]);
+        assert!(Compactor::new(slots()).fold_to(3).is_empty());
+        assert!(Compactor::new(slots()).fold_to(10).is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::{env, fs, io};
+
+use regex::Regex;
+use serde::Deserialize;
+use swayipc::{Error, Workspace};
+
+use crate::Criteria;
+
+/// The settings read from `$XDG_CONFIG_HOME/sworker/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where the daemon puts new windows, the first rule matching a window applies.
+    pub assign: Vec<Assign>,
+    pub rebalance: Rebalance,
+    /// The order the outputs are given their groups in.
+    pub output_order: OutputOrder,
+    pub outputs: Outputs,
+    pub navigation: Navigation,
+    /// The numbers the workspaces are named with.
+    pub numbering: Numbering,
+    /// Whether the numbers close up when a workspace is closed.
+    pub gaps: Gaps,
+    /// Keep `x0` of every group for a home workspace that is never shifted.
+    pub home: bool,
+    pub exclude: Exclude,
+}
+
+/// The workspaces left alone, neither renamed nor counted as a position.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Exclude {
+    /// Workspaces whose name matches one of these.
+    #[serde(deserialize_with = "crate::criteria::regexes")]
+    pub names: Vec<Regex>,
+    /// Workspaces whose name does not start with a number.
+    pub unnumbered: bool,
+    /// Every workspace on these outputs, which do not get a group either.
+    pub outputs: Vec<String>,
+}
+
+/// Whether the numbers close up when a workspace is closed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Gaps {
+    /// Move every later workspace one position down, written as `close`.
+    #[default]
+    Close,
+    /// Leave every workspace at its position, written as `keep`.
+    Keep,
+}
+
+/// The numbers the workspaces are named with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Numbering {
+    /// Ten numbers per group, `11` to `19` for group 1, `21` to `29` for group 2, written as `grouped`.
+    #[default]
+    Grouped,
+    /// One number after the other across all outputs, starting at `1`, written as `sequential`.
+    Sequential,
+}
+
+/// How the commands move between workspaces and groups.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Navigation {
+    /// Enter a group at its workspace focused most recently instead of at the focused position.
+    pub remember_position: bool,
+    /// Continue with the output at the far end when there is none in a direction.
+    pub wrap: bool,
+    /// Focus a position on every output at once, like switching virtual desktops.
+    pub synchronized: bool,
+    /// What `next` and `prev` do at the ends of a group.
+    pub group_end: GroupEnd,
+}
+
+/// What `next` and `prev` do at the last and first position of a group.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupEnd {
+    /// Create a workspace there or wrap around to the other end, written as `create`.
+    #[default]
+    Create,
+    /// Continue at the first or last position of the next or previous group, written as `continue`.
+    Continue,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Self {
+            remember_position: false,
+            wrap: true,
+            synchronized: false,
+            group_end: GroupEnd::Create,
+        }
+    }
+}
+
+/// How outputs that are not simply turned on are numbered.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Outputs {
+    /// Where the groups of outputs turned off through DPMS go.
+    pub dpms_off: Placement,
+    /// Where the groups of headless outputs go, as used for screen sharing and VNC.
+    pub headless: Placement,
+}
+
+/// Where the groups of an output go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    /// In the order of [`OutputOrder`], like every other output.
+    #[default]
+    Ordered,
+    /// After the groups of every other output.
+    Last,
+}
+
+/// The order the outputs are given their groups in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawOutputOrder")]
+pub enum OutputOrder {
+    /// As they are placed, top to bottom and then left to right, written as `top-to-bottom`.
+    #[default]
+    TopToBottom,
+    /// As they are placed, left to right and then top to bottom, written as `left-to-right`.
+    LeftToRight,
+    /// By their name, written as `name`.
+    Name,
+    /// In the order sway lists them, written as `sway`.
+    Sway,
+    /// The focused one first and then top to bottom, written as `focused`.
+    Focused,
+    /// The outputs named, in this order, followed by every other one top to bottom.
+    /// Written as a list, or as the names separated by commas, a single one followed by a comma.
+    List(Vec<String>),
+}
+
+/// An [`OutputOrder`] as written in the config, either a single word or a list of names.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawOutputOrder {
+    Word(String),
+    List(Vec<String>),
+}
+
+/// How workspaces are spread over the outputs again.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rebalance {
+    /// Rebalance in the daemon whenever an output is connected.
+    pub on_connect: bool,
+    /// Workspaces that belong to an output, moved there whenever it is connected and never moved away from it.
+    pub pin: Vec<Pin>,
+}
+
+/// Keep the workspaces whose name matches `workspace` on `output`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pin {
+    #[serde(deserialize_with = "crate::criteria::regex")]
+    pub workspace: Regex,
+    pub output: String,
+}
+
+/// Put the windows matching `criteria` on `to` as soon as they appear.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "RawAssign")]
+pub struct Assign {
+    pub criteria: Criteria,
+    pub to: Destination,
+}
+
+/// An [`Assign`] as written in the config, which cannot deny unknown fields itself as its criteria are flattened.
+#[derive(Deserialize)]
+struct RawAssign {
+    #[serde(flatten)]
+    criteria: Criteria,
+    to: Destination,
+    /// Every field the criteria do not take.
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+/// Where a window is put, translated into a workspace number only once it is needed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Destination {
+    /// A position of a group, written as `group:position`.
+    Address(i32, i32),
+    /// A new workspace appended to the focused group, written as `new`.
+    New,
+    /// The visible workspace of an output, written as `output:<name>`.
+    Output(String),
+}
+
+impl Exclude {
+    /// Whether `workspace` is left alone.
+    pub(crate) fn workspace(&self, workspace: &Workspace) -> bool {
+        (self.unnumbered && workspace.num < 0)
+            || self.output(&workspace.output)
+            || self.names.iter().any(|name| name.is_match(&workspace.name))
+    }
+
+    /// Whether the workspaces of `output` are left alone.
+    pub(crate) fn output(&self, output: &str) -> bool {
+        self.outputs.iter().any(|excluded| excluded == output)
+    }
+}
+
+impl Config {
+    /// Fail unless the config enables home workspaces, which take grouped numbering as well.
+    pub fn check_home(&self) -> Result<(), Error> {
+        if !self.home || self.numbering != Numbering::Grouped {
+            return Err(Error::CommandFailed(
+                "home workspaces need `home = true` and grouped numbering in the config".to_owned(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Load the config, or the defaults if there is none.
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(config) => toml::from_str(&config).map_err(|err| {
+                Error::Io(io::Error::new(io::ErrorKind::InvalidData, format!("{}: {err}", path.display())))
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl FromStr for Destination {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "new" {
+            return Ok(Destination::New);
+        }
+
+        if let Some(output) = value.strip_prefix("output:") {
+            if output.trim().is_empty() {
+                return Err(format!("expected the name of an output after 'output:', got '{value}'"));
+            }
+
+            return Ok(Destination::Output(output.to_owned()));
+        }
+
+        let invalid = || format!("expected group:position, new or output:<name>, got '{value}'");
+        let (group, position) = value.split_once(':').ok_or_else(invalid)?;
+
+        match (group.parse(), position.parse()) {
+            (Ok(group @ 1..), Ok(position @ 1..)) => Ok(Destination::Address(group, position)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for Destination {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for OutputOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "top-to-bottom" => Ok(OutputOrder::TopToBottom),
+            "left-to-right" => Ok(OutputOrder::LeftToRight),
+            "name" => Ok(OutputOrder::Name),
+            "sway" => Ok(OutputOrder::Sway),
+            "focused" => Ok(OutputOrder::Focused),
+            // A single word is rather a misspelled one of the above than an output, which takes a trailing comma.
+            _ if value.contains(',') => Ok(OutputOrder::List(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            )),
+            _ => Err(format!(
+                "expected top-to-bottom, left-to-right, name, sway, focused or output names separated by commas, \
+                 got '{value}'"
+            )),
+        }
+    }
+}
+
+impl TryFrom<RawOutputOrder> for OutputOrder {
+    type Error = String;
+
+    fn try_from(value: RawOutputOrder) -> Result<Self, Self::Error> {
+        match value {
+            RawOutputOrder::Word(word) => word.parse(),
+            RawOutputOrder::List(names) => Ok(OutputOrder::List(names)),
+        }
+    }
+}
+
+impl TryFrom<RawAssign> for Assign {
+    type Error = String;
+
+    fn try_from(value: RawAssign) -> Result<Self, Self::Error> {
+        if let Some(field) = value.unknown.keys().next() {
+            return Err(format!("unknown field `{field}`, expected `app_id`, `class`, `instance`, `title` or `to`"));
+        }
+
+        if value.criteria.is_empty() {
+            return Err("expected at least one of `app_id`, `class`, `instance` or `title`".into());
+        }
+
+        Ok(Self {
+            criteria: value.criteria,
+            to: value.to,
+        })
+    }
+}
+
+/// `$XDG_CONFIG_HOME/sworker/config.toml`, falling back to `~/.config`.
+fn path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destinations() {
+        assert_eq!("2:3".parse(), Ok(Destination::Address(2, 3)));
+        assert_eq!("new".parse(), Ok(Destination::New));
+        assert_eq!("output:DP-1".parse(), Ok(Destination::Output("DP-1".to_owned())));
+        assert!("output:".parse::<Destination>().is_err());
+        assert!("output: ".parse::<Destination>().is_err());
+        assert!("0:1".parse::<Destination>().is_err());
+    }
+
+    #[test]
+    fn assign_needs_an_output_name() {
+        let config = |to: &str| toml::from_str::<Config>(&format!("[[assign]]\napp_id = \"^foot$\"\nto = \"{to}\""));
+
+        assert!(config("output:DP-1").is_ok());
+        assert!(config("output:").is_err());
+    }
+}
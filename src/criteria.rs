@@ -0,0 +1,101 @@
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use swayipc::{Node, NodeType};
+
+/// The properties a window has to have, every one that is set.
+///
+/// Like sway's criteria they are regular expressions, which match anywhere unless anchored.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Criteria {
+    /// The application id of a Wayland window.
+    #[serde(default, deserialize_with = "optional_regex")]
+    pub app_id: Option<Regex>,
+    /// The class of an X11 window.
+    #[serde(default, deserialize_with = "optional_regex")]
+    pub class: Option<Regex>,
+    /// The instance of an X11 window.
+    #[serde(default, deserialize_with = "optional_regex")]
+    pub instance: Option<Regex>,
+    /// The title of a window.
+    #[serde(default, deserialize_with = "optional_regex")]
+    pub title: Option<Regex>,
+}
+
+impl Criteria {
+    /// Criteria matching the application `window` belongs to, and nothing else.
+    pub(crate) fn application(window: &Node) -> Self {
+        let exactly = |value: &str| Regex::new(&format!("^{}$", regex::escape(value))).ok();
+
+        Self {
+            app_id: window.app_id.as_deref().and_then(exactly),
+            class: property(window, |properties| properties.class.as_deref()).and_then(exactly),
+            ..Self::default()
+        }
+    }
+
+    /// Whether no property is set, so the criteria never match.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.app_id.is_none() && self.class.is_none() && self.instance.is_none() && self.title.is_none()
+    }
+
+    /// Whether `window` has every property that is set. Criteria without any never match.
+    pub(crate) fn matches(&self, window: &Node) -> bool {
+        let checks = [
+            (&self.app_id, window.app_id.as_deref()),
+            (&self.class, property(window, |properties| properties.class.as_deref())),
+            (&self.instance, property(window, |properties| properties.instance.as_deref())),
+            (&self.title, window.name.as_deref()),
+        ];
+
+        checks.iter().any(|(regex, _)| regex.is_some())
+            && checks.iter().all(|(regex, value)| match regex {
+                Some(regex) => value.is_some_and(|value| regex.is_match(value)),
+                None => true,
+            })
+    }
+}
+
+/// Every window below `node`, tiling and floating.
+///
+/// As when [`Manager`](crate::Manager) counts windows, a container without children is taken as a window.
+pub(crate) fn windows(node: &Node) -> impl Iterator<Item = &Node> {
+    node.iter().filter(|node| {
+        matches!(node.node_type, NodeType::Con | NodeType::FloatingCon)
+            && node.nodes.is_empty()
+            && node.floating_nodes.is_empty()
+    })
+}
+
+/// The application `window` belongs to, its app id on Wayland and its class on X11.
+pub(crate) fn app(window: &Node) -> Option<&str> {
+    window
+        .app_id
+        .as_deref()
+        .or_else(|| property(window, |properties| properties.class.as_deref()))
+}
+
+/// A property only X11 windows have.
+fn property<'a>(
+    window: &'a Node,
+    get: impl Fn(&'a swayipc::WindowProperties) -> Option<&'a str>,
+) -> Option<&'a str> {
+    window.window_properties.as_ref().and_then(get)
+}
+
+/// Deserialize a regular expression, rejecting invalid ones while the config is read.
+pub(crate) fn regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+pub(crate) fn regexes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Regex>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn optional_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+    regex(deserializer).map(Some)
+}
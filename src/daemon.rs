@@ -0,0 +1,107 @@
+use swayipc::{Connection, Error, Event, WindowChange, WorkspaceChange};
+
+use crate::config::Config;
+use crate::Manager;
+use crate::history::{CYCLE_TICK, History};
+use crate::journal::{Journal, RECORD_TICK, UNDO_TICK};
+
+/// Keeps the workspaces ordered and records what commands need to know about the past.
+pub struct Daemon {
+    config: Config,
+    history: History,
+    /// The layouts before the most recent commands, for `sworker undo`.
+    journal: Journal,
+    /// Number of active outputs, to tell a connected output from other output changes.
+    outputs: usize,
+}
+
+impl Daemon {
+    /// Pick up the state a previous daemon left behind and reorder all workspaces.
+    pub fn new(connection: &mut Connection, config: Config) -> Result<Self, Error> {
+        let mut history = History::load()?;
+
+        if let Some(focused) = connection.get_workspaces()?.iter().find(|workspace| workspace.focused) {
+            history.focus(None, focused.id);
+            history.store()?;
+        }
+
+        Manager::new(connection, &config)?.reorder()?;
+
+        Ok(Self {
+            config,
+            history,
+            journal: Journal::load()?,
+            outputs: active_outputs(connection)?,
+        })
+    }
+
+    /// React to a sway event.
+    ///
+    /// Only events that can change the workspace layout reorder the workspaces. Notably
+    /// [`WorkspaceChange::Rename`] is ignored, as reordering emits those itself.
+    pub fn process_event(&mut self, connection: &mut Connection, event: Event) -> Result<(), Error> {
+        match event {
+            Event::Workspace(event) => match event.change {
+                WorkspaceChange::Init => Manager::new(connection, &self.config)?.reorder(),
+                WorkspaceChange::Empty => {
+                    if let Some(current) = event.current {
+                        self.history.remove(current.id);
+                        self.history.store()?;
+                    }
+
+                    Manager::new(connection, &self.config)?.reorder()
+                }
+                WorkspaceChange::Focus => {
+                    if let Some(current) = event.current {
+                        self.history.focus(event.old.map(|old| old.id), current.id);
+                        self.history.store()?;
+                    }
+
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+            Event::Window(event) if event.change == WindowChange::New => {
+                let rule = self.config.assign.iter().find(|rule| rule.criteria.matches(&event.container));
+
+                match rule {
+                    Some(rule) => {
+                        Manager::new(connection, &self.config)?.assign(event.container.id, &rule.to)?;
+                        Manager::new(connection, &self.config)?.reorder()
+                    }
+                    None => Ok(()),
+                }
+            }
+            Event::Output(_) => {
+                let outputs = active_outputs(connection)?;
+                let connected = outputs > self.outputs;
+                self.outputs = outputs;
+
+                if connected && self.config.rebalance.on_connect {
+                    Manager::new(connection, &self.config)?.rebalance()?;
+                }
+
+                Manager::new(connection, &self.config)?.reorder()
+            }
+            Event::Tick(tick) => {
+                if let Some(id) = tick.payload.strip_prefix(CYCLE_TICK).and_then(|id| id.parse().ok()) {
+                    self.history.cycle_to(id);
+                    self.history.store()?;
+                } else if let Some(layout) = tick.payload.strip_prefix(RECORD_TICK) {
+                    self.journal.push(serde_json::from_str(layout)?);
+                    self.journal.store()?;
+                } else if tick.payload == UNDO_TICK {
+                    self.journal.pop();
+                    self.journal.store()?;
+                }
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn active_outputs(connection: &mut Connection) -> Result<usize, Error> {
+    Ok(connection.get_outputs()?.iter().filter(|output| output.active).count())
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use swayipc::{Error, Workspace};
+
+use crate::state;
+
+/// Payload of the tick `sworker cycle` sends before it focuses a workspace, followed by its con id.
+pub(crate) const CYCLE_TICK: &str = "sworker cycle ";
+
+const FILE: &str = "history";
+
+/// The workspaces in the order they were focused, most recent first.
+///
+/// Workspaces are tracked by their con id rather than their number. Reordering renames a workspace
+/// but keeps its id, so the number is only looked up once a command needs it.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct History {
+    workspaces: Vec<i64>,
+    /// The workspace `sworker cycle` went to, as long as no other focus change ended the cycle.
+    cycle: Option<i64>,
+}
+
+impl History {
+    pub(crate) fn load() -> Result<Self, Error> {
+        state::load(FILE)
+    }
+
+    pub(crate) fn store(&self) -> Result<(), Error> {
+        state::store(FILE, self)
+    }
+
+    /// Record that focus went from `old` to `current`.
+    ///
+    /// A cycle does not reorder the history while it runs. Once something else is focused, `old`
+    /// is the workspace the cycle ended on, which is why it is recorded first.
+    pub(crate) fn focus(&mut self, old: Option<i64>, current: i64) {
+        if self.cycle == Some(current) {
+            return;
+        }
+
+        self.cycle = None;
+
+        for id in old.into_iter().chain([current]) {
+            self.remove(id);
+            self.workspaces.insert(0, id);
+        }
+    }
+
+    /// Announce that a cycle is about to focus `id`.
+    pub(crate) const fn cycle_to(&mut self, id: i64) {
+        self.cycle = Some(id);
+    }
+
+    pub(crate) fn remove(&mut self, id: i64) {
+        self.workspaces.retain(|workspace| *workspace != id);
+    }
+
+    /// The most recently focused workspace accepted by `filter`, leaving out the focused one.
+    pub(crate) fn back<'a>(
+        &self,
+        workspaces: &'a [Workspace],
+        filter: impl Fn(&Workspace) -> bool,
+    ) -> Option<&'a Workspace> {
+        self.resolve(workspaces).find(|workspace| !workspace.focused && filter(workspace))
+    }
+
+    /// The workspace a cycle continues with, one step further back or, unless `back`, forth.
+    ///
+    /// The history wraps around at both ends.
+    pub(crate) fn cycle<'a>(&self, workspaces: &'a [Workspace], back: bool) -> Option<&'a Workspace> {
+        let known = self.resolve(workspaces).collect::<Vec<_>>();
+        let index = known.iter().position(|workspace| workspace.focused)?;
+
+        let index = if back {
+            (index + 1) % known.len()
+        } else {
+            (index + known.len() - 1) % known.len()
+        };
+
+        Some(known[index])
+    }
+
+    /// How many workspaces were focused since `id`, none if it was never recorded.
+    pub(crate) fn rank(&self, id: i64) -> Option<usize> {
+        self.workspaces.iter().position(|workspace| *workspace == id)
+    }
+
+    /// The recorded workspaces that still exist, most recent first.
+    fn resolve<'a>(&self, workspaces: &'a [Workspace]) -> impl Iterator<Item = &'a Workspace> {
+        self.workspaces
+            .iter()
+            .filter_map(|id| workspaces.iter().find(|workspace| workspace.id == *id))
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use swayipc::{Error, Node, NodeType, Workspace};
+
+use crate::state;
+
+/// Payload of the tick a command sends before it changes the layout, followed by the [`Layout`] as JSON.
+pub(crate) const RECORD_TICK: &str = "sworker record ";
+
+/// Payload of the tick `sworker undo` sends once it restored the most recent [`Layout`].
+pub(crate) const UNDO_TICK: &str = "sworker undo";
+
+const FILE: &str = "journal";
+
+/// Number of layouts kept, the oldest one is dropped beyond it.
+const LENGTH: usize = 16;
+
+/// The layouts before the most recent commands, the most recent last.
+///
+/// Commands cannot write this themselves, as the daemon is the only writer of its state. They send
+/// their layout in a tick instead, and the daemon records it.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct Journal {
+    layouts: Vec<Layout>,
+}
+
+/// Where the workspaces and their containers were before a command.
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Layout {
+    pub(crate) workspaces: Vec<Entry>,
+    /// The workspace every top level container was on, both by con id.
+    pub(crate) containers: BTreeMap<i64, i64>,
+    pub(crate) focused: Option<i64>,
+}
+
+/// A workspace by its con id, with the number and output it had.
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    pub(crate) id: i64,
+    pub(crate) num: i32,
+    pub(crate) output: String,
+}
+
+impl Journal {
+    pub(crate) fn load() -> Result<Self, Error> {
+        state::load(FILE)
+    }
+
+    pub(crate) fn store(&self) -> Result<(), Error> {
+        state::store(FILE, self)
+    }
+
+    pub(crate) fn push(&mut self, layout: Layout) {
+        self.layouts.push(layout);
+
+        if self.layouts.len() > LENGTH {
+            self.layouts.remove(0);
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Layout> {
+        self.layouts.pop()
+    }
+}
+
+impl Layout {
+    /// Capture the layout of `workspaces` and the containers `tree` holds on them.
+    ///
+    /// Scratchpad containers are left out, like sway does not count the scratchpad as a workspace.
+    pub(crate) fn capture(workspaces: &[Workspace], tree: &Node) -> Self {
+        let containers = tree
+            .iter()
+            .filter(|node| node.node_type == NodeType::Workspace)
+            .filter(|node| workspaces.iter().any(|workspace| workspace.id == node.id))
+            .flat_map(|workspace| {
+                workspace
+                    .nodes
+                    .iter()
+                    .chain(&workspace.floating_nodes)
+                    .map(|container| (container.id, workspace.id))
+            })
+            .collect();
+
+        Self {
+            workspaces: workspaces
+                .iter()
+                .map(|workspace| Entry {
+                    id: workspace.id,
+                    num: workspace.num,
+                    output: workspace.output.clone(),
+                })
+                .collect(),
+            containers,
+            focused: workspaces.iter().find(|workspace| workspace.focused).map(|workspace| workspace.id),
+        }
+    }
+}
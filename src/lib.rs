@@ -1,32 +1,33 @@
-use swayipc::{Connection, WorkspaceChange, Error};
-
+use swayipc::{Connection, Error};
+
+mod compactor;
+mod config;
+mod criteria;
+mod daemon;
+mod history;
+mod journal;
 mod manager;
 mod numberer;
+mod order;
+mod pinned;
 mod positioner;
+mod state;
 
-pub use manager::Manager;
+pub use compactor::Merge;
+pub use config::{
+    Assign, Config, Destination, Exclude, Gaps, GroupEnd, Navigation, Numbering, OutputOrder, Outputs, Pin, Placement,
+    Rebalance,
+};
+pub use criteria::Criteria;
+pub use daemon::Daemon;
+pub use manager::{Direction, Manager, SortKey};
 
 /// Number of positions a group can hold, i.e. the workspaces `x1` to `x9`.
-pub(crate) const POSITIONS_PER_GROUP: i32 = 9;
+pub const POSITIONS_PER_GROUP: i32 = 9;
 
 /// Numbers a group spans, i.e. `x0` to `x9`.
 pub(crate) const NUMBERS_PER_GROUP: i32 = POSITIONS_PER_GROUP + 1;
 
-/// Reorder all workspaces in reaction to a sway event.
-///
-/// Only events that can change the workspace layout are acted on. Notably
-/// [`WorkspaceChange::Rename`] is ignored, as reordering emits those itself.
-pub fn process_event(connection: &mut Connection, event: swayipc::Event) -> Result<(), swayipc::Error> {
-    match event {
-        swayipc::Event::Workspace(event) => match event.change {
-            WorkspaceChange::Init | WorkspaceChange::Empty => Manager::new(connection)?.reorder(),
-            _ => Ok(()),
-        },
-        swayipc::Event::Output(_) => Manager::new(connection)?.reorder(),
-        _ => Ok(()),
-    }
-}
-
 /// Run `command` and report commands that sway rejected.
 ///
 /// [`Connection::run_command`] only surfaces transport failures through its outer
@@ -48,4 +49,3 @@ pub fn run_command(connection: &mut Connection, command: impl AsRef<str>) -> Res
 
     Err(Error::CommandFailed(format!("'{command}': {}", failures.join(", "))))
 }
-
@@ -1,55 +1,194 @@
-use clap::Parser;
-use cli::{Action, Cli};
+use std::io;
+use std::path::Path;
+
+use clap::{CommandFactory, Parser};
+use cli::{Action, Cli, Command, GroupCommand, Scope, Shell};
+use clap_complete::generate;
 use swayipc::Connection;
-use sworker::{Manager, process_event};
+use sworker::{Config, Daemon, Manager};
 
+mod bindings;
 mod cli;
 
 fn main() -> Result<(), swayipc::Error> {
     let cli = Cli::parse();
+
+    // Packagers generate these while building, without sway running.
+    match cli.command {
+        Command::Completions { shell } => {
+            completions(shell);
+            return Ok(());
+        }
+        Command::Man { ref dir } => return Ok(man(dir.as_deref())?),
+        Command::GenerateBindings(ref bindings) => {
+            print!("{}", bindings.generate());
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let mut config = Config::load()?;
+
+    if let Some(order) = cli.output_order {
+        config.output_order = order;
+    }
+
+    // Checked before anything is recorded, so a rejected command leaves nothing for `undo`.
+    cli.command.check(&config)?;
+
     let mut connection = Connection::new()?;
-    let mut manager = Manager::new(&mut connection)?;
 
-    match cli {
-        Cli::Focus { action, new } => match action {
+    if let Command::Daemon = cli.command {
+        return daemon(connection, config);
+    }
+
+    let mut manager = Manager::new(&mut connection, &config)?;
+
+    if cli.command.is_undoable() {
+        manager.record()?;
+    }
+
+    match cli.command {
+        Command::Focus { action, new, group } => match action {
             Action::Next => manager.position_focus_next(new)?,
             Action::Prev => manager.position_focus_prev(new)?,
+            Action::Back => manager.position_focus_back()?,
+            Action::Urgent => manager.urgent_focus(group == Scope::Any)?,
+            Action::Empty => manager.position_focus_empty()?,
+            Action::Home => manager.position_focus_home()?,
+            Action::First => manager.position_focus_to(1, new)?,
+            Action::Last => manager.position_focus_to(manager.position_last(), new)?,
             Action::Position(position) => manager.position_focus_to(position, new)?,
+            Action::Relative(offset) => manager.position_focus_by(offset, new)?,
+            Action::Address(group, position) => manager.address_focus_to(group, position, new)?,
+            Action::Direction(_) => unreachable!("rejected while parsing"),
         },
-        Cli::Move { action, new } => match action {
+        Command::Move { action, new } => match action {
             Action::Next => manager.position_move_next(new)?,
             Action::Prev => manager.position_move_prev(new)?,
+            Action::Back => manager.position_move_back()?,
+            Action::Empty => manager.position_move_empty()?,
+            Action::Home => manager.position_move_home()?,
+            Action::First => manager.position_move_to(1, new)?,
+            Action::Last => manager.position_move_to(manager.position_last(), new)?,
             Action::Position(position) => manager.position_move_to(position, new)?,
+            Action::Relative(offset) => manager.position_move_by(offset, new)?,
+            Action::Address(group, position) => manager.address_move_to(group, position, new)?,
+            Action::Urgent | Action::Direction(_) => unreachable!("rejected while parsing"),
         },
-        Cli::FocusGroup { action } => match action {
+        Command::FocusGroup { action } => match action {
             Action::Next => manager.group_focus_next()?,
             Action::Prev => manager.group_focus_prev()?,
+            Action::Back => manager.group_focus_back()?,
+            Action::First => manager.group_focus_to(1)?,
+            Action::Last => manager.group_focus_to(manager.group_last())?,
             Action::Position(position) => manager.group_focus_to(position)?,
+            Action::Relative(offset) => manager.group_focus_by(offset)?,
+            Action::Direction(direction) => manager.group_focus_direction(direction)?,
+            Action::Urgent | Action::Empty | Action::Home | Action::Address(..) => {
+                unreachable!("rejected while parsing")
+            }
         },
-        Cli::MoveGroup { action } => match action {
+        Command::MoveGroup { action } => match action {
             Action::Next => manager.group_move_next()?,
             Action::Prev => manager.group_move_prev()?,
+            Action::Back => manager.group_move_back()?,
+            Action::First => manager.group_move_to(1)?,
+            Action::Last => manager.group_move_to(manager.group_last())?,
             Action::Position(position) => manager.group_move_to(position)?,
+            Action::Relative(offset) => manager.group_move_by(offset)?,
+            Action::Direction(direction) => manager.group_move_direction(direction)?,
+            Action::Urgent | Action::Empty | Action::Home | Action::Address(..) => {
+                unreachable!("rejected while parsing")
+            }
+        },
+        Command::Gather { application, new } => manager.gather(application.criteria(), new)?,
+        Command::Compact { group, count, dry_run } => compact(&mut manager, group, count, dry_run)?,
+        Command::Sort { by, group, all, save } => manager.sort(by.into(), group, all, save)?,
+        Command::Rebalance => manager.rebalance()?,
+        Command::Group { command } => match command {
+            GroupCommand::Swap { a, b } => manager.group_swap(a, b)?,
+            GroupCommand::Rotate { reverse } => manager.group_rotate(reverse)?,
         },
-        Cli::Reorder => manager.reorder()?,
-        Cli::Daemon => {
-            manager.reorder()?;
-
-            let events = Connection::new()?.subscribe([swayipc::EventType::Workspace, swayipc::EventType::Output])?;
-
-            for event in events {
-                match event {
-                    Ok(event) => {
-                        if let Err(err) = process_event(&mut connection, event) {
-                            eprintln!("Error processing event: {err}");
-                        }
-                    }
-                    Err(swayipc::Error::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-                    Err(err) => eprintln!("Error receiving event: {err}"),
+        Command::Undo => manager.undo()?,
+        Command::Pin => manager.pin(true)?,
+        Command::Unpin => manager.pin(false)?,
+        Command::Cycle { reverse } => manager.cycle(!reverse)?,
+        Command::GenerateBindings(_) | Command::Completions { .. } | Command::Man { .. } => {
+            unreachable!("handled before connecting")
+        }
+        Command::Reorder => manager.reorder()?,
+        Command::Daemon => unreachable!("handled before managing"),
+    }
+
+    // Only a command that changed the layout is recorded, so `undo` never restores the one it finds.
+    manager.commit()
+
+}
+
+/// Compact the workspaces, printing the merges instead on a `dry_run`.
+fn compact(
+    manager: &mut Manager,
+    group: Option<i32>,
+    count: Option<usize>,
+    dry_run: bool,
+) -> Result<(), swayipc::Error> {
+    let merges = manager.compact(group, count, dry_run)?;
+
+    if dry_run {
+        for merge in merges {
+            println!("{} -> {} ({} windows)", merge.from, merge.to, merge.windows);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reorder the workspaces and react to sway events until sway exits.
+fn daemon(mut connection: Connection, config: Config) -> Result<(), swayipc::Error> {
+    let mut daemon = Daemon::new(&mut connection, config)?;
+
+    let events = Connection::new()?.subscribe([
+        swayipc::EventType::Workspace,
+        swayipc::EventType::Output,
+        swayipc::EventType::Tick,
+        swayipc::EventType::Window,
+    ])?;
+
+    for event in events {
+        match event {
+            Ok(event) => {
+                if let Err(err) = daemon.process_event(&mut connection, event) {
+                    eprintln!("Error processing event: {err}");
                 }
             }
+            Err(swayipc::Error::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(err) => eprintln!("Error receiving event: {err}"),
         }
     }
 
     Ok(())
 }
+
+/// Print the completions for `shell` to stdout.
+fn completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_owned();
+    let out = &mut io::stdout();
+
+    match shell {
+        Shell::Bash => generate(clap_complete::Shell::Bash, &mut command, name, out),
+        Shell::Elvish => generate(clap_complete::Shell::Elvish, &mut command, name, out),
+        Shell::Fish => generate(clap_complete::Shell::Fish, &mut command, name, out),
+        Shell::Nushell => generate(clap_complete_nushell::Nushell, &mut command, name, out),
+        Shell::Zsh => generate(clap_complete::Shell::Zsh, &mut command, name, out),
+    }
+}
+
+/// Print the man page to stdout, or write one for every subcommand to `dir`.
+fn man(dir: Option<&Path>) -> io::Result<()> {
+    match dir {
+        Some(dir) => clap_mangen::generate_to(Cli::command(), dir),
+        None => clap_mangen::Man::new(Cli::command()).render(&mut io::stdout()),
+    }
+}
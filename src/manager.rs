@@ -1,27 +1,129 @@
-use swayipc::{Connection, Error, Node, NodeType, Workspace};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
-use crate::NUMBERS_PER_GROUP;
-use crate::numberer::Numberer;
+use swayipc::{Connection, Error, Node, NodeType, Rect, Workspace};
+
+use crate::compactor::{Compactor, Merge, Slot};
+use crate::config::{Config, Destination, GroupEnd};
+use crate::criteria::{self, Criteria};
+use crate::history::{CYCLE_TICK, History};
+use crate::journal::{Journal, Layout, RECORD_TICK, UNDO_TICK};
+use crate::numberer::{self, Numberer, TEMP_PREFIX};
+use crate::order::Order;
+use crate::pinned::Pinned;
 use crate::positioner::Positioner;
-use crate::run_command;
+use crate::{NUMBERS_PER_GROUP, POSITIONS_PER_GROUP, run_command};
+
+/// The name of the workspace sway keeps the scratchpad in.
+const SCRATCHPAD: &str = "__i3_scratch";
+
+/// A direction on the screen, in which the outputs of the groups are arranged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    const fn opposite(self) -> Self {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+
+    /// How far `to` lies past the edge of `from` in the direction, and how far off to the side.
+    ///
+    /// There is none unless `to` lies entirely past that edge, like sway's `focus output` requires. The distance to
+    /// the side is 0 as long as `to` overlaps `from` across the direction.
+    fn distance(self, from: &Rect, to: &Rect) -> Option<(i32, i32)> {
+        // The gap between two ranges on an axis, 0 if they overlap.
+        let gap = |from: i32, from_len: i32, to: i32, to_len: i32| {
+            (to - (from + from_len)).max(from - (to + to_len)).max(0)
+        };
+
+        let (along, across) = match self {
+            Direction::Left => (from.x - (to.x + to.width), gap(from.y, from.height, to.y, to.height)),
+            Direction::Right => (to.x - (from.x + from.width), gap(from.y, from.height, to.y, to.height)),
+            Direction::Up => (from.y - (to.y + to.height), gap(from.x, from.width, to.x, to.width)),
+            Direction::Down => (to.y - (from.y + from.height), gap(from.x, from.width, to.x, to.width)),
+        };
+
+        (along >= 0).then_some((along, across))
+    }
+
+    /// The output of `others` next to `current` in the direction, or the farthest one the other way if there is
+    /// none and the outputs `wrap`.
+    ///
+    /// Outputs overlapping `current` across the direction win over those off to the side, then the nearest one.
+    fn adjacent<'b>(self, current: &Rect, others: &[(&Rect, &'b str)], wrap: bool) -> Option<&'b str> {
+        let ahead = others
+            .iter()
+            .filter_map(|(rect, name)| Some((self.distance(current, rect)?, *name)))
+            .min_by_key(|((along, across), _)| (*across, *along));
+        let behind = || {
+            others
+                .iter()
+                .filter_map(|(rect, name)| Some((self.opposite().distance(current, rect)?, *name)))
+                .min_by_key(|((along, across), _)| (*across, Reverse(*along)))
+        };
+
+        match ahead {
+            Some((_, name)) => Some(name),
+            None if wrap => behind().map(|(_, name)| name),
+            None => None,
+        }
+    }
+}
+
+/// What `sort` orders the workspaces of a group by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// The name after the number, ignoring case.
+    Name,
+    /// The application of the first window, empty workspaces last.
+    App,
+    /// The number of windows, most first.
+    Windows,
+    /// When the workspace was created, oldest first.
+    Created,
+    /// When the workspace was focused last as recorded by the daemon, most recent first.
+    LastUsed,
+    /// The order remembered by the last sort that saved it.
+    Saved,
+    /// The order the workspaces have, to save it without sorting.
+    Current,
+}
 
 pub struct Manager<'a> {
     connection: &'a mut Connection,
+    config: &'a Config,
     workspaces: Vec<Workspace>,
     numberer: Numberer,
     positioner: Positioner,
+    pinned: Pinned,
     /// Number of windows on the focused workspace.
     nodes: usize,
+    /// The layout before the command, until [`Self::commit`] hands it to the daemon.
+    recorded: Option<Layout>,
 }
 
 impl<'a> Manager<'a> {
-    pub fn new(connection: &'a mut Connection) -> Result<Self, Error> {
-        let workspaces = connection.get_workspaces()?;
+    pub fn new(connection: &'a mut Connection, config: &'a Config) -> Result<Self, Error> {
+        let (mut workspaces, focused) = managed(connection, config)?;
         let outputs = connection.get_outputs()?;
+        let pinned = Pinned::load()?;
+        let numberer = Numberer::new(&workspaces, &outputs, config, &pinned);
+        numberer.translate(&mut workspaces);
 
         let mut nodes = 0;
 
-        if let Some(focused) = workspaces.iter().find(|w| w.focused).or_else(|| workspaces.first()) {
+        // An excluded workspace has windows of its own all the same.
+        if let Some(focused) = focused.as_ref().or_else(|| workspaces.first()) {
             nodes = connection
                 .get_tree()?
                 .find(|node| node.node_type == NodeType::Workspace && focused.id == node.id)
@@ -29,14 +131,29 @@ impl<'a> Manager<'a> {
         }
 
         Ok(Self {
-            numberer: Numberer::new(&workspaces, &outputs),
-            positioner: Positioner::new(&workspaces),
+            numberer,
+            positioner: positioner(&workspaces, focused.as_ref()),
             connection,
+            config,
             workspaces,
+            pinned,
             nodes,
+            recorded: None,
         })
     }
 
+    /// The last position of the focused group.
+    #[must_use]
+    pub fn position_last(&self) -> i32 {
+        self.positioner.position_last()
+    }
+
+    /// The last group.
+    #[must_use]
+    pub fn group_last(&self) -> i32 {
+        self.positioner.group_last()
+    }
+
     pub fn reorder(&mut self) -> Result<(), Error> {
         let commands = self.numberer.rename_commands(&self.workspaces);
 
@@ -49,7 +166,9 @@ impl<'a> Manager<'a> {
 
     /// Focus the next position, inserting a workspace there if `new`.
     pub fn position_focus_next(&mut self, new: bool) -> Result<(), Error> {
-        let num = if (new || self.positioner.is_end()) && !self.positioner.is_full() && self.nodes > 0 {
+        let num = if let Some(num) = self.linear_num(true).filter(|_| !new) {
+            num
+        } else if (new || self.positioner.is_end()) && !self.positioner.is_full() && self.nodes > 0 {
             let num = self.numberer.append_at(self.positioner.num());
             self.reorder()?;
 
@@ -58,12 +177,14 @@ impl<'a> Manager<'a> {
             self.positioner.wrapping_position_add(1)
         };
 
-        run_command(self.connection, format!("workspace number {num}"))
+        run_command(self.connection, format!("workspace number {}", self.numberer.external(num)))
     }
 
     /// Focus the previous position, inserting a workspace there if `new`.
     pub fn position_focus_prev(&mut self, new: bool) -> Result<(), Error> {
-        let num = if (new || self.positioner.is_start()) && !self.positioner.is_full() && self.nodes > 0 {
+        let num = if let Some(num) = self.linear_num(false).filter(|_| !new) {
+            num
+        } else if (new || self.positioner.is_start()) && !self.positioner.is_full() && self.nodes > 0 {
             let num = self.numberer.prepend_at(self.positioner.num());
             self.reorder()?;
 
@@ -72,10 +193,13 @@ impl<'a> Manager<'a> {
             self.positioner.wrapping_position_add(-1)
         };
 
-        run_command(self.connection, format!("workspace number {num}"))
+        run_command(self.connection, format!("workspace number {}", self.numberer.external(num)))
     }
 
     /// Focus `position`, inserting a workspace before the one already there if `new`.
+    ///
+    /// If the config synchronizes the outputs, every other output switches to `position` of its first group as well,
+    /// creating it at the end of the group if it is missing. Focus stays on the focused output.
     pub fn position_focus_to(&mut self, position: i32, new: bool) -> Result<(), Error> {
         let num = if new {
             self.insert_position(position, self.nodes == 0)?
@@ -83,12 +207,86 @@ impl<'a> Manager<'a> {
             self.positioner.position_to(position)
         };
 
+        if new || !self.config.navigation.synchronized {
+            return run_command(self.connection, format!("workspace number {}", self.numberer.external(num)));
+        }
+
+        let focused = self.output_of(num);
+        // The first group of every other output.
+        let mut groups: Vec<(&str, i32)> = Vec::new();
+
+        for workspace in self.workspaces.iter().filter(|workspace| Some(&workspace.output) != focused.as_ref()) {
+            let group = workspace.num / NUMBERS_PER_GROUP;
+
+            match groups.iter_mut().find(|(output, _)| *output == workspace.output) {
+                Some((_, first)) => *first = group.min(*first),
+                None => groups.push((&workspace.output, group)),
+            }
+        }
+
+        let mut commands = groups
+            .into_iter()
+            .map(|(output, group)| {
+                let other = Positioner::at(&self.workspaces, group * NUMBERS_PER_GROUP + 1);
+                let position = position.clamp(1, (other.position_highest + 1).min(POSITIONS_PER_GROUP));
+
+                let num = self.numberer.external(group * NUMBERS_PER_GROUP + position);
+
+                format!("focus output {output}; workspace number {num}")
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(output) = focused {
+            commands.push(format!("focus output {output}"));
+        }
+
+        commands.push(format!("workspace number {}", self.numberer.external(num)));
+
+        run_command(self.connection, commands.join("; "))
+    }
+
+    /// Focus the home workspace `x0` of the focused group, creating it if it does not exist yet.
+    pub fn position_focus_home(&mut self) -> Result<(), Error> {
+        let num = self.home()?;
+
         run_command(self.connection, format!("workspace number {num}"))
     }
 
+    /// Focus the position `offset` positions away, wrapping at both ends of the group.
+    ///
+    /// With `new` a workspace is inserted at that position instead, clamped to the positions a group can hold.
+    pub fn position_focus_by(&mut self, offset: i32, new: bool) -> Result<(), Error> {
+        if new {
+            return self.position_focus_to(self.positioner.position.saturating_add(offset), true);
+        }
+
+        run_command(
+            self.connection,
+            format!("workspace number {}", self.numberer.external(self.positioner.wrapping_position_add(offset))),
+        )
+    }
+
+    /// Focus an empty workspace of the focused group, appending one if there is none.
+    pub fn position_focus_empty(&mut self) -> Result<(), Error> {
+        match self.empty_position()? {
+            Some(num) => run_command(self.connection, format!("workspace number {}", self.numberer.external(num))),
+            None => Ok(()),
+        }
+    }
+
+    /// Focus the workspace that was focused before the focused one.
+    pub fn position_focus_back(&mut self) -> Result<(), Error> {
+        match self.back(|_| true)? {
+            Some(num) => run_command(self.connection, format!("workspace number {}", self.numberer.external(num))),
+            None => Ok(()),
+        }
+    }
+
     /// Move the focused container to the next position, inserting a workspace there if `new`.
     pub fn position_move_next(&mut self, new: bool) -> Result<(), Error> {
-        let num = if (new || self.positioner.is_end()) && !self.positioner.is_full() && self.nodes > 1 {
+        let num = if let Some(num) = self.linear_num(true).filter(|_| !new) {
+            num
+        } else if (new || self.positioner.is_end()) && !self.positioner.is_full() && self.nodes > 1 {
             let num = self.numberer.append_at(self.positioner.num());
             self.reorder()?;
 
@@ -99,13 +297,15 @@ impl<'a> Manager<'a> {
 
         run_command(
             self.connection,
-            format!("[con_id=__focused__] move container to workspace number {num}, focus"),
+            format!("[con_id=__focused__] move container to workspace number {}, focus", self.numberer.external(num)),
         )
     }
 
     /// Move the focused container to the previous position, inserting a workspace there if `new`.
     pub fn position_move_prev(&mut self, new: bool) -> Result<(), Error> {
-        let num = if (new || self.positioner.is_start()) && !self.positioner.is_full() && self.nodes > 1 {
+        let num = if let Some(num) = self.linear_num(false).filter(|_| !new) {
+            num
+        } else if (new || self.positioner.is_start()) && !self.positioner.is_full() && self.nodes > 1 {
             let num = self.numberer.prepend_at(self.positioner.num());
             self.reorder()?;
 
@@ -116,7 +316,7 @@ impl<'a> Manager<'a> {
 
         run_command(
             self.connection,
-            format!("[con_id=__focused__] move container to workspace number {num}, focus"),
+            format!("[con_id=__focused__] move container to workspace number {}, focus", self.numberer.external(num)),
         )
     }
 
@@ -130,39 +330,755 @@ impl<'a> Manager<'a> {
 
         run_command(
             self.connection,
-            format!("[con_id=__focused__] move container to workspace number {num}"),
+            format!("[con_id=__focused__] move container to workspace number {}", self.numberer.external(num)),
         )
     }
 
+    /// Move the focused container to the home workspace `x0` of the focused group.
+    pub fn position_move_home(&mut self) -> Result<(), Error> {
+        let num = self.home()?;
+
+        run_command(self.connection, format!("[con_id=__focused__] move container to workspace number {num}"))
+    }
+
+    /// Move the focused container `offset` positions away, wrapping at both ends of the group.
+    ///
+    /// With `new` a workspace is inserted at that position instead, clamped to the positions a group can hold.
+    pub fn position_move_by(&mut self, offset: i32, new: bool) -> Result<(), Error> {
+        if new {
+            return self.position_move_to(self.positioner.position.saturating_add(offset), true);
+        }
+
+        run_command(
+            self.connection,
+            format!(
+                "[con_id=__focused__] move container to workspace number {}",
+                self.numberer.external(self.positioner.wrapping_position_add(offset))
+            ),
+        )
+    }
+
+    /// Move the focused container to an empty workspace of the focused group and follow it.
+    ///
+    /// A workspace is appended to the group if there is none.
+    pub fn position_move_empty(&mut self) -> Result<(), Error> {
+        match self.empty_position()? {
+            Some(num) => run_command(
+                self.connection,
+                format!(
+                    "[con_id=__focused__] move container to workspace number {}, focus",
+                    self.numberer.external(num)
+                ),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Move the focused container to the workspace that was focused before the focused one.
+    pub fn position_move_back(&mut self) -> Result<(), Error> {
+        match self.back(|_| true)? {
+            Some(num) => run_command(
+                self.connection,
+                format!("[con_id=__focused__] move container to workspace number {}", self.numberer.external(num)),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Focus the workspaces in the order they were focused, one further step back or forth per call.
+    ///
+    /// Unlike [`Self::position_focus_back`] this does not go back and forth between two workspaces,
+    /// as the history keeps its order until a cycle ends by focusing anything else.
+    pub fn cycle(&mut self, back: bool) -> Result<(), Error> {
+        let Some(workspace) = History::load()?.cycle(&self.workspaces, back) else {
+            return Ok(());
+        };
+
+        self.connection.send_tick(format!("{CYCLE_TICK}{}", workspace.id))?;
+
+        run_command(self.connection, format!("workspace number {}", self.numberer.external(workspace.num)))
+    }
+
+    /// Capture the layout before a command, so [`Self::undo`] can restore it once [`Self::commit`] records it.
+    pub fn record(&mut self) -> Result<(), Error> {
+        self.recorded = Some(Layout::capture(&self.workspaces, &self.connection.get_tree()?));
+
+        Ok(())
+    }
+
+    /// Have the daemon record the layout captured by [`Self::record`], unless the command left it as it was.
+    pub fn commit(&mut self) -> Result<(), Error> {
+        let Some(layout) = self.recorded.take() else {
+            return Ok(());
+        };
+
+        self.reload()?;
+
+        if layout == Layout::capture(&self.workspaces, &self.connection.get_tree()?) {
+            return Ok(());
+        }
+
+        self.connection.send_tick(format!("{RECORD_TICK}{}", serde_json::to_string(&layout)?))?;
+
+        Ok(())
+    }
+
+    /// Restore the layout before the most recently recorded command.
+    ///
+    /// Containers are moved back to their workspace, which is created again on its output if it was closed
+    /// since. The workspaces get their numbers back, those created since then follow on their output.
+    /// Containers closed since then are gone, and without a daemon recording layouts there is nothing to undo.
+    pub fn undo(&mut self) -> Result<(), Error> {
+        let Some(layout) = Journal::load()?.pop() else {
+            return Ok(());
+        };
+
+        let tree = self.connection.get_tree()?;
+        let current = Layout::capture(&self.workspaces, &tree);
+        let outputs = self.connection.get_outputs()?;
+        let mut commands = Vec::new();
+
+        for (container, workspace) in &layout.containers {
+            if current.containers.get(container).is_none_or(|now| now == workspace) {
+                continue;
+            }
+
+            let Some(entry) = layout.workspaces.iter().find(|entry| entry.id == *workspace) else {
+                continue;
+            };
+
+            if let Some(workspace) = self.workspaces.iter().find(|workspace| workspace.id == entry.id) {
+                let num = self.numberer.external(workspace.num);
+
+                commands.push(format!("[con_id={container}] move container to workspace number {num}"));
+                continue;
+            }
+
+            // Sway creates the workspace on the focused output, so it is moved to its own afterwards.
+            commands.push(format!(
+                "[con_id={container}] move container to workspace number {TEMP_PREFIX}{}",
+                entry.num
+            ));
+
+            if outputs.iter().any(|output| output.active && output.name == entry.output) {
+                commands.push(format!("[con_id={container}] move workspace to output {}", entry.output));
+            }
+        }
+
+        if !commands.is_empty() {
+            run_command(self.connection, commands.join("; "))?;
+        }
+
+        self.workspaces = managed(self.connection, self.config)?.0;
+
+        // A workspace created again is known by the containers it got back, the daemon may have renamed it already.
+        let moved = Layout::capture(&self.workspaces, &self.connection.get_tree()?);
+        let mut nums = BTreeMap::new();
+
+        for workspace in &self.workspaces {
+            let entry = layout.workspaces.iter().find(|entry| {
+                entry.id == workspace.id
+                    || moved.containers.iter().any(|(container, now)| {
+                        *now == workspace.id && layout.containers.get(container) == Some(&entry.id)
+                    })
+            });
+
+            if let Some(entry) = entry {
+                nums.insert(workspace.id, entry.num);
+            }
+        }
+
+        // Workspaces created since follow the highest number on their output.
+        for workspace in &self.workspaces {
+            if nums.contains_key(&workspace.id) {
+                continue;
+            }
+
+            let last = self
+                .workspaces
+                .iter()
+                .filter(|other| other.output == workspace.output)
+                .filter_map(|other| nums.get(&other.id))
+                .max()
+                .copied()
+                .unwrap_or(workspace.num);
+
+            nums.insert(workspace.id, last + 1);
+        }
+
+        let commands =
+            Numberer::restore(nums, &self.workspaces, self.config.numbering).rename_commands(&self.workspaces);
+
+        if !commands.is_empty() {
+            run_command(self.connection, commands.join("; "))?;
+        }
+
+        self.reload()?;
+        self.reorder()?;
+        self.reload()?;
+
+        if let Some(focused) = self.workspaces.iter().find(|workspace| Some(workspace.id) == layout.focused) {
+            run_command(self.connection, format!("workspace number {}", self.numberer.external(focused.num)))?;
+        }
+
+        self.connection.send_tick(UNDO_TICK)?;
+
+        Ok(())
+    }
+
+    /// Pin the focused workspace to its position, or unpin it unless `pinned`.
+    ///
+    /// A pinned workspace is never shifted by inserting, moving or closing other workspaces, they rather
+    /// take the positions around it.
+    pub fn pin(&mut self, pinned: bool) -> Result<(), Error> {
+        let Some(focused) = self.workspaces.iter().find(|workspace| workspace.focused) else {
+            return Ok(());
+        };
+
+        self.pinned.set(focused.id, pinned, &self.workspaces);
+        self.pinned.store()?;
+        self.reload()?;
+
+        self.reorder()
+    }
+
     pub fn group_focus_next(&mut self) -> Result<(), Error> {
-        self.group_focus_to(self.positioner.wrapping_group(1))
+        self.group_focus_by(1)
     }
 
     pub fn group_focus_prev(&mut self) -> Result<(), Error> {
-        self.group_focus_to(self.positioner.wrapping_group(-1))
+        self.group_focus_by(-1)
+    }
+
+    /// Focus the group `offset` groups away, wrapping at the first and last group.
+    pub fn group_focus_by(&mut self, offset: i32) -> Result<(), Error> {
+        self.group_focus_to(self.positioner.wrapping_group(offset))
     }
 
     pub fn group_focus_to(&mut self, group: i32) -> Result<(), Error> {
-        let num = self.positioner.saturating_group_to(group);
+        self.focus_num(self.group_num(group)?)
+    }
 
-        if let Some(output) = self.output_of(num) {
-            run_command(self.connection, format!("focus output {output}"))?;
+    /// Focus the group of the output next to the focused one in `direction`.
+    pub fn group_focus_direction(&mut self, direction: Direction) -> Result<(), Error> {
+        match self.adjacent_group(direction)? {
+            Some(group) => self.group_focus_to(group),
+            None => Ok(()),
         }
+    }
 
-        run_command(self.connection, format!("workspace number {num}"))
+    /// Focus the workspace of another group that was focused most recently.
+    pub fn group_focus_back(&mut self) -> Result<(), Error> {
+        let group = self.positioner.group;
+
+        match self.back(|workspace| workspace.num / NUMBERS_PER_GROUP != group)? {
+            Some(num) => run_command(self.connection, format!("workspace number {}", self.numberer.external(num))),
+            None => Ok(()),
+        }
     }
 
     pub fn group_move_next(&mut self) -> Result<(), Error> {
-        self.group_move_to(self.positioner.wrapping_group(1))
+        self.group_move_by(1)
     }
 
     pub fn group_move_prev(&mut self) -> Result<(), Error> {
-        self.group_move_to(self.positioner.wrapping_group(-1))
+        self.group_move_by(-1)
+    }
+
+    /// Move the focused container to the group `offset` groups away, wrapping at the first and last group.
+    pub fn group_move_by(&mut self, offset: i32) -> Result<(), Error> {
+        self.group_move_to(self.positioner.wrapping_group(offset))
+    }
+
+    /// Move the focused container to the group of the output next to the focused one in `direction`.
+    pub fn group_move_direction(&mut self, direction: Direction) -> Result<(), Error> {
+        match self.adjacent_group(direction)? {
+            Some(group) => self.group_move_to(group),
+            None => Ok(()),
+        }
     }
 
     pub fn group_move_to(&mut self, group: i32) -> Result<(), Error> {
-        let num = self.positioner.saturating_group_to(group);
+        self.move_num(self.group_num(group)?)
+    }
+
+    /// Move every window `criteria` match to the focused workspace, or to a new one inserted after it if `new`.
+    ///
+    /// Without `criteria` the windows of the focused application are gathered.
+    pub fn gather(&mut self, criteria: Option<Criteria>, new: bool) -> Result<(), Error> {
+        let tree = self.connection.get_tree()?;
+        let focused = self.positioner.num();
+
+        let criteria = match criteria {
+            Some(criteria) => criteria,
+            None => tree.iter().find(|node| node.focused).map(Criteria::application).unwrap_or_default(),
+        };
+
+        let windows = tree
+            .iter()
+            .filter(|node| node.node_type == NodeType::Workspace && node.name.as_deref() != Some(SCRATCHPAD))
+            // Excluded workspaces have no number and are left untouched.
+            .filter(|workspace| self.num_of(workspace).is_some_and(|num| new || num != focused))
+            .flat_map(criteria::windows)
+            .filter(|window| criteria.matches(window))
+            .map(|window| window.id)
+            .collect::<Vec<_>>();
+
+        if windows.is_empty() {
+            return Ok(());
+        }
+
+        // A group without a free position cannot grow, so the windows are gathered on the focused workspace.
+        let num = if new && !self.positioner.is_full() {
+            let num = self.numberer.append_at(focused);
+            self.reorder()?;
+
+            num
+        } else {
+            focused
+        };
+
+        let commands = windows
+            .iter()
+            .map(|id| format!("[con_id={id}] move container to workspace number {}", self.numberer.external(num)))
+            .collect::<Vec<_>>();
+
+        run_command(self.connection, commands.join("; "))?;
+
+        if num != focused {
+            run_command(self.connection, format!("workspace number {}", self.numberer.external(num)))?;
+        }
+
+        // Workspaces the windows left are gone now, which leaves gaps behind.
+        self.reload()?;
+        self.reorder()
+    }
+
+    /// Fold workspaces of `group`, the focused one by default, into their neighbours and return the merges.
+    ///
+    /// Without `count` every workspace holding a single window is folded into its previous neighbour, the first
+    /// one into the next. With `count` the workspaces with the fewest windows are folded until no more than
+    /// `count` are left. Unless `dry_run` the merges are carried out and the gaps they leave closed up.
+    pub fn compact(&mut self, group: Option<i32>, count: Option<usize>, dry_run: bool) -> Result<Vec<Merge>, Error> {
+        let group = group.map_or(self.positioner.group, |group| group.clamp(1, self.positioner.group_last()));
+
+        let slots = self
+            .connection
+            .get_tree()?
+            .iter()
+            .filter(|node| node.node_type == NodeType::Workspace)
+            .filter_map(|workspace| {
+                // Home and pinned workspaces are never shifted, so nothing is folded into or out of them.
+                let num = self
+                    .num_of(workspace)
+                    .filter(|num| *num > 0 && num % NUMBERS_PER_GROUP != 0 && num / NUMBERS_PER_GROUP == group)
+                    .filter(|_| !self.numberer.is_pinned(workspace.id))?;
+
+                Some(Slot {
+                    num,
+                    windows: Self::count_nodes(workspace),
+                    containers: workspace.nodes.iter().chain(&workspace.floating_nodes).map(|node| node.id).collect(),
+                })
+            })
+            .collect();
+
+        let compactor = Compactor::new(slots);
+        let merges = match count {
+            Some(count) => compactor.fold_to(count),
+            None => compactor.fold_single(),
+        };
+
+        // Reported by the numbers sway shows, before the merges renumber the workspaces.
+        let reported = merges
+            .iter()
+            .map(|merge| Merge {
+                from: self.numberer.external(merge.from),
+                to: self.numberer.external(merge.to),
+                windows: merge.windows,
+                containers: merge.containers.clone(),
+            })
+            .collect();
+
+        if dry_run || merges.is_empty() {
+            return Ok(reported);
+        }
+
+        let commands = merges
+            .iter()
+            .flat_map(|merge| {
+                let num = self.numberer.external(merge.to);
 
+                merge
+                    .containers
+                    .iter()
+                    .map(move |id| format!("[con_id={id}] move container to workspace number {num}"))
+            })
+            .collect::<Vec<_>>();
+
+        run_command(self.connection, commands.join("; "))?;
+
+        // The focused workspace is not destroyed while focused, so focus follows its windows.
+        let focused = merges.iter().fold(self.positioner.num(), |num, merge| {
+            if merge.from == num { merge.to } else { num }
+        });
+
+        if focused != self.positioner.num() {
+            run_command(self.connection, format!("workspace number {}", self.numberer.external(focused)))?;
+        }
+
+        self.reload()?;
+        self.reorder()?;
+
+        Ok(reported)
+    }
+
+    /// Sort the workspaces of `group`, the focused one by default or every one if `all`, by `key`.
+    ///
+    /// Workspaces without a key follow in the order they have, pinned and home workspaces keep their position.
+    /// With `save` the resulting order of all workspaces is remembered for [`SortKey::Saved`].
+    pub fn sort(&mut self, key: SortKey, group: Option<i32>, all: bool, save: bool) -> Result<(), Error> {
+        let groups = if all {
+            (1..=self.positioner.group_last()).collect::<Vec<_>>()
+        } else {
+            vec![group.map_or(self.positioner.group, |group| group.clamp(1, self.positioner.group_last()))]
+        };
+
+        let tree = self.connection.get_tree()?;
+        let history = History::load()?;
+        let saved = Order::load()?;
+        let node = |id: i64| tree.find_as_ref(|node| node.id == id);
+
+        for group in groups {
+            let mut workspaces = self
+                .workspaces
+                .iter()
+                .filter(|workspace| workspace.num / NUMBERS_PER_GROUP == group)
+                .collect::<Vec<_>>();
+
+            match key {
+                SortKey::Name => workspaces.sort_by_cached_key(|workspace| {
+                    workspace.name.trim_start_matches(|char: char| char.is_ascii_digit()).to_lowercase()
+                }),
+                SortKey::App => workspaces.sort_by_cached_key(|workspace| {
+                    let app = node(workspace.id).and_then(|node| criteria::windows(node).find_map(criteria::app));
+
+                    (app.is_none(), app.map(str::to_lowercase))
+                }),
+                SortKey::Windows => workspaces.sort_by_cached_key(|workspace| {
+                    Reverse(node(workspace.id).map_or(0, Self::count_nodes))
+                }),
+                SortKey::Created => workspaces.sort_by_key(|workspace| workspace.id),
+                SortKey::LastUsed => {
+                    workspaces.sort_by_key(|workspace| history.rank(workspace.id).unwrap_or(usize::MAX));
+                }
+                SortKey::Saved => workspaces.sort_by_key(|workspace| saved.rank(workspace.id).unwrap_or(usize::MAX)),
+                SortKey::Current => workspaces.sort_by_key(|workspace| workspace.num),
+            }
+
+            self.numberer.arrange(&workspaces.iter().map(|workspace| workspace.id).collect::<Vec<_>>());
+        }
+
+        if save {
+            Order::new(self.numberer.order()).store()?;
+        }
+
+        self.reorder()
+    }
+
+    /// Move workspaces between the outputs so that each holds a single group again.
+    ///
+    /// Workspaces the configured pins assign to a connected output are moved there first. Then every output holding
+    /// more than one group hands each group beyond its first to an output without windows, as long as there is one
+    /// left.
+    /// A workspace is moved by one of its windows, so empty ones stay where they are.
+    pub fn rebalance(&mut self) -> Result<(), Error> {
+        let tree = self.connection.get_tree()?;
+        let outputs = self.connection.get_outputs()?;
+        let outputs = numberer::sort_outputs(&outputs, self.config);
+
+        let windows = tree
+            .iter()
+            .filter(|node| node.node_type == NodeType::Workspace)
+            .filter_map(|workspace| criteria::windows(workspace).next().map(|window| (workspace.id, window.id)))
+            .collect::<HashMap<_, _>>();
+
+        let pins = &self.config.rebalance.pin;
+        let pin_of = |workspace: &Workspace| pins.iter().find(|pin| pin.workspace.is_match(&workspace.name));
+        let mut placed = self
+            .workspaces
+            .iter()
+            .map(|workspace| (workspace.id, workspace.output.as_str()))
+            .collect::<HashMap<_, _>>();
+        let mut commands = Vec::new();
+
+        for workspace in &self.workspaces {
+            let (Some(pin), Some(window)) = (pin_of(workspace), windows.get(&workspace.id)) else {
+                continue;
+            };
+
+            if pin.output != workspace.output && outputs.iter().any(|output| output.name == pin.output) {
+                commands.push(format!("[con_id={window}] move workspace to output {}", pin.output));
+                placed.insert(workspace.id, &pin.output);
+            }
+        }
+
+        let held = |output: &str| {
+            let mut held = self
+                .workspaces
+                .iter()
+                .filter(|workspace| placed[&workspace.id] == output)
+                .collect::<Vec<_>>();
+            held.sort_by_key(|workspace| workspace.num);
+
+            held
+        };
+
+        let mut receivers = outputs
+            .iter()
+            .filter(|output| held(&output.name).iter().all(|workspace| !windows.contains_key(&workspace.id)))
+            .collect::<VecDeque<_>>();
+
+        for output in &outputs {
+            let held = held(&output.name);
+
+            // By group number rather than by count, as a home workspace at `x0` takes no position.
+            for group in held.chunk_by(|a, b| a.num / NUMBERS_PER_GROUP == b.num / NUMBERS_PER_GROUP).skip(1) {
+                let Some(receiver) = receivers.pop_front() else {
+                    break;
+                };
+
+                for workspace in group {
+                    if let (None, Some(window)) = (pin_of(workspace), windows.get(&workspace.id)) {
+                        commands.push(format!("[con_id={window}] move workspace to output {}", receiver.name));
+                    }
+                }
+            }
+        }
+
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        run_command(self.connection, commands.join("; "))?;
+
+        self.reload()?;
+        self.reorder()
+    }
+
+    /// Exchange the outputs of the groups `a` and `b`, both clamped to the existing groups.
+    ///
+    /// Their workspaces keep their positions, the groups are numbered after the outputs they end up on.
+    pub fn group_swap(&mut self, a: i32, b: i32) -> Result<(), Error> {
+        let a = self.positioner.saturating_group_to(a) / NUMBERS_PER_GROUP;
+        let b = self.positioner.saturating_group_to(b) / NUMBERS_PER_GROUP;
+
+        self.exchange(&[(a, b), (b, a)])
+    }
+
+    /// Move every group to the output of the next one, the last group to the output of the first,
+    /// or the other way around if `reverse`.
+    pub fn group_rotate(&mut self, reverse: bool) -> Result<(), Error> {
+        let offset = if reverse { -1 } else { 1 };
+        let last = self.positioner.group_last();
+
+        let moves = (1..=last)
+            .map(|group| (group, (group + offset - 1).rem_euclid(last) + 1))
+            .collect::<Vec<_>>();
+
+        self.exchange(&moves)
+    }
+
+    /// Move the workspaces of the first group of every pair to the output holding the second one, as it is now.
+    ///
+    /// Like when rebalancing, a workspace is moved by one of its windows, so empty ones stay where they are.
+    /// The workspaces visible before are shown again afterwards, the focused one last.
+    fn exchange(&mut self, moves: &[(i32, i32)]) -> Result<(), Error> {
+        let windows = self
+            .connection
+            .get_tree()?
+            .iter()
+            .filter(|node| node.node_type == NodeType::Workspace)
+            .filter_map(|workspace| criteria::windows(workspace).next().map(|window| (workspace.id, window.id)))
+            .collect::<HashMap<_, _>>();
+
+        let mut commands = Vec::new();
+
+        for &(group, to) in moves {
+            let Some(output) = self.output_of(to * NUMBERS_PER_GROUP) else {
+                continue;
+            };
+
+            for workspace in &self.workspaces {
+                if workspace.num / NUMBERS_PER_GROUP != group || workspace.output == output {
+                    continue;
+                }
+
+                if let Some(window) = windows.get(&workspace.id) {
+                    commands.push(format!("[con_id={window}] move workspace to output {output}"));
+                }
+            }
+        }
+
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        let mut shown = self
+            .workspaces
+            .iter()
+            .filter(|workspace| workspace.visible && windows.contains_key(&workspace.id))
+            .collect::<Vec<_>>();
+        shown.sort_by_key(|workspace| workspace.focused);
+        let shown = shown.iter().map(|workspace| workspace.id).collect::<Vec<_>>();
+
+        run_command(self.connection, commands.join("; "))?;
+
+        self.reload()?;
+        self.reorder()?;
+
+        let commands = shown
+            .iter()
+            .filter_map(|id| self.workspaces.iter().find(|workspace| workspace.id == *id))
+            .map(|workspace| format!("workspace number {}", self.numberer.external(workspace.num)))
+            .collect::<Vec<_>>();
+
+        if !commands.is_empty() {
+            run_command(self.connection, commands.join("; "))?;
+        }
+
+        // Sway fills an output it emptied with a new workspace, which is gone again once another one is shown.
+        self.reload()?;
+        self.reorder()
+    }
+
+    /// Move the window `id` to `destination`, as numbered right now.
+    ///
+    /// Focus stays where it is, unless the workspace has to be created on another output first.
+    pub fn assign(&mut self, id: i64, destination: &Destination) -> Result<(), Error> {
+        let num = match destination {
+            Destination::Output(output) => {
+                return run_command(self.connection, format!("[con_id={id}] move container to output {output}"));
+            }
+            Destination::New => {
+                let last = self.positioner.saturating_position_to(POSITIONS_PER_GROUP);
+
+                // A group without a free position cannot grow, so the window goes to its last workspace.
+                if self.positioner.is_full() {
+                    last
+                } else {
+                    let num = self.numberer.append_at(last);
+                    self.reorder()?;
+
+                    num
+                }
+            }
+            Destination::Address(group, position) => {
+                Positioner::at(&self.workspaces, self.positioner.saturating_group_to(*group)).position_to(*position)
+            }
+        };
+
+        let exists = self.workspaces.iter().any(|workspace| workspace.num == num);
+        let output = self.output_of(num);
+        let focused = self.numberer.external(self.positioner.num());
+        let num = self.numberer.external(num);
+
+        match output {
+            // Sway creates a workspace on the focused output, so that output is focused for as long as it takes.
+            Some(output) if !exists => run_command(
+                self.connection,
+                format!(
+                    "focus output {output}; [con_id={id}] move container to workspace number {num}; \
+                     workspace number {focused}"
+                ),
+            ),
+            _ => run_command(self.connection, format!("[con_id={id}] move container to workspace number {num}")),
+        }
+    }
+
+    /// Focus `position` of `group`, inserting a workspace before the one already there if `new`.
+    pub fn address_focus_to(&mut self, group: i32, position: i32, new: bool) -> Result<(), Error> {
+        match self.address(group, position, new)? {
+            Some(num) => self.focus_num(num),
+            None => self.position_focus_to(position, new),
+        }
+    }
+
+    /// Move the focused container to `position` of `group`,
+    /// inserting a workspace before the one already there if `new`.
+    pub fn address_move_to(&mut self, group: i32, position: i32, new: bool) -> Result<(), Error> {
+        match self.address(group, position, new)? {
+            Some(num) => self.move_num(num),
+            None => self.position_move_to(position, new),
+        }
+    }
+
+    /// Focus a workspace with an urgent window, looking in other groups too if `any_group`.
+    ///
+    /// Urgent workspaces of the focused group come first. Every further call focuses the next one,
+    /// as long as the focused workspace stays urgent, and the first one left over once it is not.
+    pub fn urgent_focus(&mut self, any_group: bool) -> Result<(), Error> {
+        let group = self.positioner.group;
+
+        // The focused workspace is taken along to know where the cycle continues.
+        let mut urgent = self
+            .workspaces
+            .iter()
+            .filter(|workspace| workspace.urgent || workspace.focused)
+            .filter(|workspace| any_group || workspace.num / NUMBERS_PER_GROUP == group)
+            .map(|workspace| workspace.num)
+            .collect::<Vec<_>>();
+
+        urgent.sort_by_key(|num| (num / NUMBERS_PER_GROUP != group, *num));
+
+        let next = urgent
+            .iter()
+            .position(|num| *num == self.positioner.num())
+            .map_or(0, |index| (index + 1) % urgent.len());
+
+        match urgent.get(next) {
+            Some(&num) if num != self.positioner.num() => self.focus_num(num),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move the focused container to the workspace of another group that was focused most recently.
+    pub fn group_move_back(&mut self) -> Result<(), Error> {
+        let group = self.positioner.group;
+
+        match self.back(|workspace| workspace.num / NUMBERS_PER_GROUP != group)? {
+            Some(num) => run_command(
+                self.connection,
+                format!("[con_id=__focused__] move container to workspace number {}", self.numberer.external(num)),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Read the workspaces again, after commands changed them.
+    ///
+    /// The windows of the focused workspace are not counted again.
+    fn reload(&mut self) -> Result<(), Error> {
+        let focused;
+        (self.workspaces, focused) = managed(self.connection, self.config)?;
+        self.numberer = Numberer::new(&self.workspaces, &self.connection.get_outputs()?, self.config, &self.pinned);
+        self.numberer.translate(&mut self.workspaces);
+        self.positioner = positioner(&self.workspaces, focused.as_ref());
+
+        Ok(())
+    }
+
+    /// Focus `num` on the output holding its group, so it is created there if it does not exist yet.
+    fn focus_num(&mut self, num: i32) -> Result<(), Error> {
+        if let Some(output) = self.output_of(num) {
+            run_command(self.connection, format!("focus output {output}"))?;
+        }
+
+        run_command(self.connection, format!("workspace number {}", self.numberer.external(num)))
+    }
+
+    /// Move the focused container to `num` on the output holding its group,
+    /// so it is created there if it does not exist yet.
+    fn move_num(&mut self, num: i32) -> Result<(), Error> {
         if let Some(output) = self.output_of(num) {
             run_command(
                 self.connection,
@@ -172,10 +1088,146 @@ impl<'a> Manager<'a> {
 
         run_command(
             self.connection,
-            format!("[con_id=__focused__] move container to workspace number {num}"),
+            format!("[con_id=__focused__] move container to workspace number {}", self.numberer.external(num)),
         )
     }
 
+    /// The first group of the output next to the one holding the focused group in `direction`.
+    ///
+    /// See [`Direction::adjacent`], the config decides whether to wrap around.
+    fn adjacent_group(&mut self, direction: Direction) -> Result<Option<i32>, Error> {
+        let outputs = self.connection.get_outputs()?;
+        let outputs = outputs.iter().filter(|output| output.active).collect::<Vec<_>>();
+
+        let Some(current) = self
+            .output_of(self.positioner.num())
+            .and_then(|name| outputs.iter().find(|output| output.name == name))
+        else {
+            return Ok(None);
+        };
+
+        let others = outputs
+            .iter()
+            .filter(|output| output.name != current.name)
+            .map(|output| (&output.rect, output.name.as_str()))
+            .collect::<Vec<_>>();
+        let target = direction.adjacent(&current.rect, &others, self.config.navigation.wrap);
+
+        Ok(target.and_then(|name| {
+            self.workspaces
+                .iter()
+                .filter(|workspace| workspace.output == name)
+                .map(|workspace| workspace.num / NUMBERS_PER_GROUP)
+                .min()
+        }))
+    }
+
+    /// The number of the home workspace of the focused group, as long as the config enables them.
+    fn home(&self) -> Result<i32, Error> {
+        self.config.check_home()?;
+
+        Ok(self.positioner.group * NUMBERS_PER_GROUP)
+    }
+
+    /// The number `next` or `prev` continue with in the next or previous group, unless `forward`.
+    ///
+    /// Only at the last or first position, and only if the config makes the groups one line of workspaces.
+    /// The first group follows the last one, and the other way around.
+    fn linear_num(&self, forward: bool) -> Option<i32> {
+        if self.config.navigation.group_end != GroupEnd::Continue {
+            return None;
+        }
+
+        if forward && self.positioner.is_end() {
+            Some(self.positioner.wrapping_group(1) * NUMBERS_PER_GROUP + 1)
+        } else if !forward && self.positioner.is_start() {
+            let group = self.positioner.wrapping_group(-1);
+
+            let last = Positioner::at(&self.workspaces, group * NUMBERS_PER_GROUP).position_last();
+
+            Some(group * NUMBERS_PER_GROUP + last)
+        } else {
+            None
+        }
+    }
+
+    /// The number `group` is entered at, clamped to the existing groups.
+    ///
+    /// That is the focused position or, if the config says so, the workspace of `group` the daemon recorded as
+    /// focused most recently. The position is the fallback for a group without history.
+    fn group_num(&self, group: i32) -> Result<i32, Error> {
+        let num = self.positioner.saturating_group_to(group);
+        let group = num / NUMBERS_PER_GROUP;
+
+        if !self.config.navigation.remember_position || group == self.positioner.group {
+            return Ok(num);
+        }
+
+        Ok(self.back(|workspace| workspace.num / NUMBERS_PER_GROUP == group)?.unwrap_or(num))
+    }
+
+    /// The number of `position` in `group`, clamped to the existing groups, inserting a workspace there if `new`.
+    ///
+    /// There is none for the focused group, which is left to the position commands, as only they know
+    /// how to take along the focused workspace.
+    fn address(&mut self, group: i32, position: i32, new: bool) -> Result<Option<i32>, Error> {
+        let target = Positioner::at(&self.workspaces, self.positioner.saturating_group_to(group));
+
+        if target.group == self.positioner.group {
+            return Ok(None);
+        }
+
+        // A group without a free position cannot grow, so the workspace already there is used.
+        if !new || target.is_full() {
+            return Ok(Some(target.position_to(position)));
+        }
+
+        let num = self.numberer.prepend_at(target.position_to(position));
+        self.reorder()?;
+
+        Ok(Some(num))
+    }
+
+    /// The number of the most recently focused workspace accepted by `filter`, as it is numbered now.
+    ///
+    /// Without a daemon recording the history there is none.
+    fn back(&self, filter: impl Fn(&Workspace) -> bool) -> Result<Option<i32>, Error> {
+        Ok(History::load()?
+            .back(&self.workspaces, filter)
+            .map(|workspace| workspace.num))
+    }
+
+    /// The number of an empty workspace in the focused group, preferring the focused one and then the lowest.
+    ///
+    /// Without one a workspace is appended to the group, unless the group is full already.
+    fn empty_position(&mut self) -> Result<Option<i32>, Error> {
+        if self.nodes == 0 {
+            return Ok(Some(self.positioner.num()));
+        }
+
+        let group = self.positioner.group;
+
+        let empty = self
+            .connection
+            .get_tree()?
+            .iter()
+            .filter(|node| node.node_type == NodeType::Workspace && Self::count_nodes(node) == 0)
+            .filter_map(|node| self.num_of(node))
+            .filter(|num| *num > 0 && num / NUMBERS_PER_GROUP == group)
+            .min();
+
+        if empty.is_some() || self.positioner.is_full() {
+            return Ok(empty);
+        }
+
+        let num = self
+            .numberer
+            .append_at(self.positioner.saturating_position_to(POSITIONS_PER_GROUP));
+        self.reorder()?;
+
+        Ok(Some(num))
+    }
+
     /// Free `position` for a new workspace and return its number.
     ///
     /// `vacates` tells whether the command leaves the focused workspace empty.
@@ -206,6 +1258,11 @@ impl<'a> Manager<'a> {
             .map(|workspace| workspace.output.clone())
     }
 
+    /// The group and position of the workspace `node`, rather than the number sway shows for it.
+    fn num_of(&self, node: &Node) -> Option<i32> {
+        self.workspaces.iter().find(|workspace| workspace.id == node.id).map(|workspace| workspace.num)
+    }
+
     /// Count the windows below `node`, descending into split and floating containers.
     ///
     /// A child without children of its own is a window itself.
@@ -217,3 +1274,91 @@ impl<'a> Manager<'a> {
             .sum()
     }
 }
+
+/// The workspaces sworker manages, leaving out those the config excludes, and the focused one, excluded or not.
+fn managed(connection: &mut Connection, config: &Config) -> Result<(Vec<Workspace>, Option<Workspace>), Error> {
+    let mut workspaces = connection.get_workspaces()?;
+    let focused = workspaces.iter().find(|workspace| workspace.focused).cloned();
+    workspaces.retain(|workspace| !config.exclude.workspace(workspace));
+
+    Ok((workspaces, focused))
+}
+
+/// The positioner of the `focused` workspace.
+///
+/// An excluded workspace has no position, so the first group of its output stands in for it, focused ahead of its
+/// first position.
+fn positioner(workspaces: &[Workspace], focused: Option<&Workspace>) -> Positioner {
+    let group = focused
+        .filter(|focused| workspaces.iter().all(|workspace| workspace.id != focused.id))
+        .and_then(|focused| {
+            workspaces
+                .iter()
+                .filter(|workspace| workspace.output == focused.output)
+                .map(|workspace| workspace.num / NUMBERS_PER_GROUP)
+                .min()
+        });
+
+    match group {
+        Some(group) => Positioner::at(workspaces, group * NUMBERS_PER_GROUP),
+        None => Positioner::new(workspaces),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        serde_json::from_value(json!({ "x": x, "y": y, "width": width, "height": height })).unwrap()
+    }
+
+    #[test]
+    fn distance_only_counts_outputs_past_the_edge() {
+        let laptop = rect(0, 1440, 1920, 1080);
+        let above = rect(0, 0, 2560, 1440);
+        let beside = rect(2560, 0, 2560, 1440);
+
+        assert_eq!(Direction::Right.distance(&laptop, &above), None);
+        assert_eq!(Direction::Right.distance(&laptop, &beside), Some((640, 0)));
+        assert_eq!(Direction::Up.distance(&laptop, &above), Some((0, 0)));
+        assert_eq!(Direction::Up.distance(&laptop, &beside), Some((0, 640)));
+        assert_eq!(Direction::Down.distance(&above, &laptop), Some((0, 0)));
+        assert_eq!(Direction::Left.distance(&beside, &above), Some((0, 0)));
+        assert_eq!(Direction::Left.distance(&laptop, &beside), None);
+    }
+
+    #[test]
+    fn adjacent_in_an_l_shape() {
+        let laptop = rect(0, 1440, 1920, 1080);
+        let above = rect(0, 0, 2560, 1440);
+        let beside = rect(2560, 0, 2560, 1440);
+        let from_laptop = [(&above, "above"), (&beside, "beside")];
+        let from_beside = [(&laptop, "laptop"), (&above, "above")];
+
+        assert_eq!(Direction::Right.adjacent(&laptop, &from_laptop, false), Some("beside"));
+        assert_eq!(Direction::Up.adjacent(&laptop, &from_laptop, false), Some("above"));
+        assert_eq!(Direction::Down.adjacent(&laptop, &from_laptop, false), None);
+        assert_eq!(Direction::Down.adjacent(&laptop, &from_laptop, true), Some("above"));
+        assert_eq!(Direction::Left.adjacent(&beside, &from_beside, false), Some("above"));
+        assert_eq!(Direction::Down.adjacent(&beside, &from_beside, false), Some("laptop"));
+    }
+
+    #[test]
+    fn adjacent_with_mixed_sizes() {
+        // A 4K monitor in the middle, a portrait monitor to the left and a small one to the right, lower down.
+        let middle = rect(1080, 0, 3840, 2160);
+        let portrait = rect(0, 0, 1080, 1920);
+        let small = rect(4920, 1800, 1280, 1024);
+        let from_middle = [(&portrait, "portrait"), (&small, "small")];
+        let from_small = [(&middle, "middle"), (&portrait, "portrait")];
+
+        assert_eq!(Direction::Left.adjacent(&middle, &from_middle, false), Some("portrait"));
+        assert_eq!(Direction::Right.adjacent(&middle, &from_middle, false), Some("small"));
+        assert_eq!(Direction::Left.adjacent(&small, &from_small, false), Some("middle"));
+        assert_eq!(Direction::Right.adjacent(&small, &from_small, true), Some("portrait"));
+        assert_eq!(Direction::Up.adjacent(&small, &from_small, false), None);
+    }
+}
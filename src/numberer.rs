@@ -1,63 +1,291 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use swayipc::{Output, Workspace};
 
+use crate::config::{Config, Gaps, Numbering, OutputOrder, Placement};
+use crate::pinned::Pinned;
 use crate::{NUMBERS_PER_GROUP, POSITIONS_PER_GROUP};
 
-const TEMP_PREFIX: &str = "999";
+pub(crate) const TEMP_PREFIX: &str = "999";
+
+/// The prefix sway names headless outputs with.
+const HEADLESS_PREFIX: &str = "HEADLESS-";
 
 /// The number a workspace that does not exist yet is relocated from, i.e. one beyond every real one.
 const UNNUMBERED: i32 = i32::MAX;
 
 /// A struct to manage the numbering of workspaces.
-pub(crate) struct Numberer(BTreeMap<i64, i32>);
+///
+/// Workspaces are numbered by group and position, as `group * 10 + position`, whatever number sway shows for
+/// them. [`Self::external`] translates such a number into the one sway knows the workspace by.
+pub(crate) struct Numberer {
+    nums: BTreeMap<i64, i32>,
+    /// The workspaces that keep their position, see [`Pinned`].
+    pinned: BTreeSet<i64>,
+    numbering: Numbering,
+    /// The numbers sway showed for the workspaces when they were read.
+    current: BTreeMap<i64, i32>,
+    /// The number the last relocation freed or moved a workspace to.
+    reserved: Option<i32>,
+}
 
 impl Numberer {
     /// Number every workspace after the position it holds on its output.
     ///
-    /// The workspaces are taken in the order sway reports them.
-    pub(crate) fn new(workspaces: &[Workspace], outputs: &[Output]) -> Self {
-        let mut numberer = Self(BTreeMap::new());
+    /// The workspaces are taken in the order sway reports them, the outputs as [`sort_outputs`] puts them.
+    /// A pinned workspace keeps its position on its output, the others fill the positions around it. If the config
+    /// keeps gaps with grouped numbers, every workspace keeps its position like a pinned one, and only new ones fill
+    /// the gaps. Home workspaces, if enabled, keep `x0` of their group.
+    pub(crate) fn new(workspaces: &[Workspace], outputs: &[Output], config: &Config, pinned: &Pinned) -> Self {
+        let mut numberer = Self {
+            nums: BTreeMap::new(),
+            pinned: BTreeSet::new(),
+            numbering: config.numbering,
+            current: workspaces.iter().map(|workspace| (workspace.id, workspace.num)).collect(),
+            reserved: None,
+        };
         let mut group = 1;
 
-        // Outputs are numbered as they are placed, top to bottom and left to right.
-        let mut outputs = outputs.iter().collect::<Vec<_>>();
-        outputs.sort_by(|left, right| left.rect.y.cmp(&right.rect.y).then(left.rect.x.cmp(&right.rect.x)));
+        for output in sort_outputs(outputs, config) {
+            let on_output = workspaces
+                .iter()
+                .filter(|workspace| workspace.output == output.name)
+                .collect::<Vec<_>>();
+            let num = |index: i32| {
+                // An output with more workspaces than a group holds continues in the
+                // next group, again starting at position 1.
+                (group + index / POSITIONS_PER_GROUP) * NUMBERS_PER_GROUP + index % POSITIONS_PER_GROUP + 1
+            };
+
+            // Sequential numbers count on from the workspaces of the outputs before.
+            let before = i32::try_from(numberer.nums.len()).unwrap_or(i32::MAX);
+            // The positions of as many groups as the workspaces of the output fill.
+            let limit = i32::try_from(on_output.len().div_ceil(POSITIONS_PER_GROUP.unsigned_abs() as usize))
+                .unwrap_or(i32::MAX)
+                .saturating_mul(POSITIONS_PER_GROUP);
+            // The group positions are counted from: the one the output is given if it holds that already, so a
+            // workspace moved in from a lower group cannot push the others out, otherwise its lowest one.
+            let present = on_output
+                .iter()
+                .filter(|workspace| workspace.num > 0)
+                .map(|workspace| workspace.num / NUMBERS_PER_GROUP)
+                .collect::<BTreeSet<_>>();
+            let base = if present.contains(&group) {
+                Some(group)
+            } else {
+                present.first().copied()
+            };
+
+            // The index of the position a workspace holds on its output, counted across its groups.
+            let held = |workspace: &Workspace| {
+                let index = match numberer.numbering {
+                    Numbering::Grouped => {
+                        let position = workspace.num % NUMBERS_PER_GROUP;
+                        let groups = workspace.num / NUMBERS_PER_GROUP - base?;
+
+                        (position > 0).then_some(groups * POSITIONS_PER_GROUP + position - 1)?
+                    }
+                    Numbering::Sequential => workspace.num - before - 1,
+                };
+
+                // Far beyond its output, like the temporary names of a rename, it rather takes a free position.
+                (workspace.num > 0 && (0..limit).contains(&index)).then_some(index)
+            };
+
+            // Two workspaces holding the same position, e.g. after a move between outputs, cannot both keep it.
+            let mut claimed = BTreeSet::new();
+
+            // Sequential numbers close up across outputs, so a position held there is no gap to keep.
+            let gaps = config.gaps == Gaps::Keep && numberer.numbering == Numbering::Grouped;
 
-        for output in outputs {
+            for workspace in &on_output {
+                let keeps = gaps || pinned.contains(workspace.id);
+
+                if let Some(index) = held(workspace).filter(|index| keeps && claimed.insert(*index)) {
+                    numberer.nums.insert(workspace.id, num(index));
+
+                    if pinned.contains(workspace.id) {
+                        numberer.pinned.insert(workspace.id);
+                    }
+                }
+            }
+
+            let is_home = |workspace: &Workspace| {
+                config.home
+                    && numberer.numbering == Numbering::Grouped
+                    && workspace.num > 0
+                    && workspace.num % NUMBERS_PER_GROUP == 0
+            };
             let mut index = 0;
 
-            for workspace in workspaces.iter().filter(|workspace| workspace.output == output.name) {
-                // An output with more workspaces than a group holds continues in the
-                // next group, again starting at position 1.
-                let num = (group + index / POSITIONS_PER_GROUP) * NUMBERS_PER_GROUP + index % POSITIONS_PER_GROUP + 1;
+            for workspace in &on_output {
+                if numberer.nums.contains_key(&workspace.id) || is_home(workspace) {
+                    continue;
+                }
 
-                numberer.0.insert(workspace.id, num);
+                while claimed.contains(&index) {
+                    index += 1;
+                }
+
+                numberer.nums.insert(workspace.id, num(index));
                 index += 1;
             }
 
+            let taken = claimed.last().map_or(index, |last| index.max(last + 1));
+            let groups = (taken.max(1) - 1) / POSITIONS_PER_GROUP + 1;
+            // The groups of the output whose `x0` is taken by a home workspace already.
+            let mut homes = BTreeSet::new();
+
+            // A home workspace stays at `x0` of its group, outside of the positions. A second one of the
+            // same group becomes an ordinary workspace at the end instead.
+            for workspace in on_output.iter().filter(|workspace| is_home(workspace)) {
+                let base = base.unwrap_or(workspace.num / NUMBERS_PER_GROUP);
+                let offset = (workspace.num / NUMBERS_PER_GROUP - base).clamp(0, groups - 1);
+
+                if homes.insert(offset) {
+                    numberer.nums.insert(workspace.id, (group + offset) * NUMBERS_PER_GROUP);
+                    numberer.pinned.insert(workspace.id);
+                } else {
+                    while claimed.contains(&index) {
+                        index += 1;
+                    }
+
+                    numberer.nums.insert(workspace.id, num(index));
+                    index += 1;
+                }
+            }
+
             // Skip every group this output took, so the next one starts on a free group.
-            if index > 0 {
-                group += (index - 1) / POSITIONS_PER_GROUP + 1;
+            let taken = claimed.last().map_or(index, |last| index.max(last + 1));
+
+            if taken > 0 {
+                group += (taken - 1) / POSITIONS_PER_GROUP + 1;
+            } else if !homes.is_empty() {
+                group += 1;
             }
         }
 
         numberer
     }
 
+    /// Number the workspaces as given in `nums`, leaving every other one of `workspaces` as it is.
+    pub(crate) fn restore(nums: BTreeMap<i64, i32>, workspaces: &[Workspace], numbering: Numbering) -> Self {
+        Self {
+            nums,
+            pinned: BTreeSet::new(),
+            numbering,
+            current: workspaces.iter().map(|workspace| (workspace.id, workspace.num)).collect(),
+            reserved: None,
+        }
+    }
+
+    /// Replace the number sway shows for each of `workspaces` by its group and position.
+    pub(crate) fn translate(&self, workspaces: &mut [Workspace]) {
+        if self.numbering == Numbering::Grouped {
+            return;
+        }
+
+        for workspace in workspaces {
+            if let Some(&num) = self.nums.get(&workspace.id) {
+                workspace.num = num;
+            }
+        }
+    }
+
+    /// The number sway knows the workspace numbered `num` by, once the workspaces are renamed.
+    ///
+    /// With sequential numbers, a `num` no workspace holds and no relocation freed is given a number beyond every
+    /// other one. Sway creates that workspace last on the focused output, where reordering gives it its position.
+    /// Each such `num` gets a number of its own, so creating several at once never ends up on the same one.
+    pub(crate) fn external(&self, num: i32) -> i32 {
+        if self.numbering == Numbering::Grouped {
+            return num;
+        }
+
+        let nums = self.nums.values().copied().chain(self.reserved).collect::<BTreeSet<_>>();
+        let before = i32::try_from(nums.range(..num).count()).unwrap_or(i32::MAX);
+
+        if nums.contains(&num) {
+            return before + 1;
+        }
+
+        let last = self.current.values().copied().max().unwrap_or(0);
+
+        // The numbers no workspace holds, counted up to `num`, which grows with every further one.
+        last.max(i32::try_from(nums.len()).unwrap_or(i32::MAX)).saturating_add(num - before)
+    }
+
+    /// Give the workspaces `ids` the numbers they hold between them, in the order of `ids`.
+    ///
+    /// Pinned workspaces keep their number and are left out.
+    pub(crate) fn arrange(&mut self, ids: &[i64]) {
+        let ids = ids
+            .iter()
+            .filter(|id| !self.pinned.contains(id) && self.nums.contains_key(id))
+            .collect::<Vec<_>>();
+        let mut nums = ids.iter().filter_map(|id| self.nums.get(id).copied()).collect::<Vec<_>>();
+        nums.sort_unstable();
+
+        for (id, num) in ids.into_iter().zip(nums) {
+            self.nums.insert(*id, num);
+        }
+    }
+
+    /// Whether the workspace `id` keeps its position, as a pinned or home workspace.
+    pub(crate) fn is_pinned(&self, id: i64) -> bool {
+        self.pinned.contains(&id)
+    }
+
+    /// The workspaces in the order of the numbers they are given.
+    pub(crate) fn order(&self) -> Vec<i64> {
+        let mut ids = self.nums.keys().copied().collect::<Vec<_>>();
+        ids.sort_by_key(|id| self.nums.get(id));
+
+        ids
+    }
+
     /// Renumber the workspace at `from` to `to`, shifting everything in between the other way.
     ///
     /// The position `from` gives up is the one `to` takes, so the group keeps its size and this
     /// also fits a group that is already full. A `from` no workspace holds gives up nothing and
     /// grows the group by the position it frees instead.
+    ///
+    /// Pinned workspaces are skipped rather than shifted, and a `to` held by one moves on to the next
+    /// position that is not, which is the number returned.
     pub(crate) fn relocate(&mut self, from: i32, to: i32) -> i32 {
-        for ws_num in self.0.values_mut() {
+        let pinned = self
+            .nums
+            .iter()
+            .filter(|(id, num)| self.pinned.contains(id) && **num != from)
+            .map(|(_, num)| *num)
+            .collect::<BTreeSet<_>>();
+        let step = |mut num: i32, add: i32| {
+            num += add;
+
+            while pinned.contains(&num) {
+                num += add;
+            }
+
+            num
+        };
+
+        let to = if pinned.contains(&to) {
+            step(to, if from > to { 1 } else { -1 })
+        } else {
+            to
+        };
+
+        self.reserved = Some(to);
+
+        for (id, ws_num) in &mut self.nums {
             if *ws_num == from {
                 *ws_num = to;
+            } else if self.pinned.contains(id) {
+                // Stays where it is.
             } else if *ws_num >= to && *ws_num < from {
-                *ws_num += 1;
+                *ws_num = step(*ws_num, 1);
             } else if *ws_num > from && *ws_num <= to {
-                *ws_num -= 1;
+                *ws_num = step(*ws_num, -1);
             }
         }
 
@@ -80,11 +308,14 @@ impl Numberer {
         let mut reindex_down = Vec::new();
 
         for workspace in workspaces {
-            let Some(&num) = self.0.get(&workspace.id) else {
+            let Some(&num) = self.nums.get(&workspace.id) else {
                 continue;
             };
 
-            if workspace.num == num {
+            let num = self.external(num);
+            let current = self.current.get(&workspace.id).copied().unwrap_or(workspace.num);
+
+            if current == num {
                 continue;
             }
 
@@ -95,10 +326,10 @@ impl Numberer {
                 continue;
             };
 
-            let source = if workspace.num < 0 {
+            let source = if current < 0 {
                 String::new()
             } else {
-                workspace.num.to_string()
+                current.to_string()
             };
 
             reindex_up.push(format!(
@@ -115,6 +346,40 @@ impl Numberer {
     }
 }
 
+/// The active outputs that are not excluded, in the order their groups are numbered in.
+///
+/// Outputs that are turned off or headless follow every other one when the config places them last.
+pub(crate) fn sort_outputs<'a>(outputs: &'a [Output], config: &Config) -> Vec<&'a Output> {
+    let mut outputs = outputs
+        .iter()
+        .filter(|output| output.active && !config.exclude.output(&output.name))
+        .collect::<Vec<_>>();
+
+    match &config.output_order {
+        OutputOrder::TopToBottom => outputs.sort_by_key(|output| (output.rect.y, output.rect.x)),
+        OutputOrder::LeftToRight => outputs.sort_by_key(|output| (output.rect.x, output.rect.y)),
+        OutputOrder::Name => outputs.sort_by(|left, right| left.name.cmp(&right.name)),
+        OutputOrder::Sway => {}
+        OutputOrder::Focused => outputs.sort_by_key(|output| (!output.focused, output.rect.y, output.rect.x)),
+        OutputOrder::List(names) => outputs.sort_by_key(|output| {
+            let index = names.iter().position(|name| *name == output.name).unwrap_or(names.len());
+
+            (index, output.rect.y, output.rect.x)
+        }),
+    }
+
+    // Stable, so both the outputs placed last and the others keep the order from above.
+    outputs.sort_by_key(|output| {
+        let headless = output.name.starts_with(HEADLESS_PREFIX);
+        // Sway reports `power` since 1.9, only `dpms` before.
+        let off = !output.power && !output.dpms;
+
+        (headless && config.outputs.headless == Placement::Last) || (off && config.outputs.dpms_off == Placement::Last)
+    });
+
+    outputs
+}
+
 /// The quote character `name` has to be wrapped in for a sway command.
 ///
 /// Sway keeps backslashes instead of unescaping them, so a quote can only be avoided
@@ -130,3 +395,185 @@ fn quote(name: &str) -> Option<char> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::Outputs;
+
+    /// A full HD output at `x`, which sway reports as `active`, and as powered on if `on`.
+    fn output(name: &str, x: i32, active: bool, on: bool) -> Output {
+        serde_json::from_value(json!({
+            "name": name,
+            "make": "",
+            "model": "",
+            "serial": "",
+            "active": active,
+            "dpms": on,
+            "power": on,
+            "primary": false,
+            "rect": { "x": x, "y": 0, "width": 1920, "height": 1080 },
+        }))
+        .unwrap()
+    }
+
+    fn workspace(id: i64, num: i32, output: &str) -> Workspace {
+        serde_json::from_value(json!({
+            "id": id,
+            "num": num,
+            "name": num.to_string(),
+            "visible": false,
+            "focused": false,
+            "urgent": false,
+            "representation": null,
+            "rect": { "x": 0, "y": 0, "width": 0, "height": 0 },
+            "output": output,
+        }))
+        .unwrap()
+    }
+
+    fn names<'a>(outputs: &'a [Output], config: &Config) -> Vec<&'a str> {
+        sort_outputs(outputs, config).iter().map(|output| output.name.as_str()).collect()
+    }
+
+    /// The numbers `Numberer::new` gives the first workspace of every output, in the order of `outputs`.
+    fn nums(outputs: &[Output], config: &Config) -> Vec<Option<i32>> {
+        let workspaces = outputs
+            .iter()
+            .zip(1..)
+            .map(|(output, id)| workspace(id, 11, &output.name))
+            .collect::<Vec<_>>();
+        let numberer = Numberer::new(&workspaces, outputs, config, &Pinned::default());
+
+        workspaces
+            .iter()
+            .map(|workspace| numberer.nums.get(&workspace.id).copied())
+            .collect()
+    }
+
+    fn placing(dpms_off: Placement, headless: Placement) -> Config {
+        Config {
+            outputs: Outputs { dpms_off, headless },
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn inactive_output_takes_no_group() {
+        let outputs = [
+            output("DP-1", 0, true, true),
+            output("DP-2", 1920, false, false),
+            output("DP-3", 3840, true, true),
+        ];
+        let config = Config::default();
+
+        assert_eq!(names(&outputs, &config), ["DP-1", "DP-3"]);
+        assert_eq!(nums(&outputs, &config), [Some(11), None, Some(21)]);
+    }
+
+    #[test]
+    fn dpms_off_last_moves_output_behind() {
+        let outputs = [
+            output("DP-1", 0, true, false),
+            output("DP-2", 1920, true, true),
+            output("DP-3", 3840, true, true),
+        ];
+        let config = placing(Placement::Last, Placement::Ordered);
+
+        assert_eq!(names(&outputs, &config), ["DP-2", "DP-3", "DP-1"]);
+        assert_eq!(nums(&outputs, &config), [Some(31), Some(11), Some(21)]);
+    }
+
+    #[test]
+    fn headless_last_moves_headless_outputs_behind() {
+        let outputs = [
+            output("HEADLESS-1", 0, true, true),
+            output("DP-1", 1920, true, true),
+            output("HEADLESS-2", 3840, true, true),
+            output("DP-2", 5760, true, true),
+        ];
+        let config = placing(Placement::Ordered, Placement::Last);
+
+        assert_eq!(names(&outputs, &config), ["DP-1", "DP-2", "HEADLESS-1", "HEADLESS-2"]);
+        assert_eq!(nums(&outputs, &config), [Some(31), Some(11), Some(41), Some(21)]);
+    }
+
+    #[test]
+    fn ordered_keeps_output_order() {
+        let outputs = [
+            output("DP-2", 1920, true, true),
+            output("HEADLESS-1", 0, true, true),
+            output("DP-1", 3840, true, false),
+        ];
+        let config = Config::default();
+
+        assert_eq!(names(&outputs, &config), ["HEADLESS-1", "DP-2", "DP-1"]);
+        assert_eq!(nums(&outputs, &config), [Some(21), Some(11), Some(31)]);
+
+        let config = Config {
+            output_order: OutputOrder::Name,
+            ..Config::default()
+        };
+
+        assert_eq!(names(&outputs, &config), ["DP-1", "DP-2", "HEADLESS-1"]);
+        assert_eq!(nums(&outputs, &config), [Some(21), Some(31), Some(11)]);
+    }
+
+    #[test]
+    fn sequential_numbers_keep_no_gaps() {
+        let outputs = [output("DP-1", 0, true, true), output("DP-2", 1920, true, true)];
+        // Workspace 2 on the first output was closed, sway still shows the second output from 3 on.
+        let workspaces = [workspace(1, 1, "DP-1"), workspace(3, 3, "DP-2"), workspace(4, 4, "DP-2")];
+        let config = Config {
+            numbering: Numbering::Sequential,
+            gaps: Gaps::Keep,
+            ..Config::default()
+        };
+        let numberer = Numberer::new(&workspaces, &outputs, &config, &Pinned::default());
+
+        assert_eq!(numberer.nums.values().copied().collect::<Vec<_>>(), [11, 21, 22]);
+    }
+
+    #[test]
+    fn kept_positions_count_from_the_output_group() {
+        let outputs = [output("DP-1", 0, true, true), output("DP-2", 1920, true, true)];
+        // Workspace 12 was moved from the first output to the second one.
+        let workspaces = [
+            workspace(1, 11, "DP-1"),
+            workspace(2, 12, "DP-2"),
+            workspace(3, 21, "DP-2"),
+            workspace(4, 22, "DP-2"),
+        ];
+        let config = Config {
+            gaps: Gaps::Keep,
+            ..Config::default()
+        };
+        let numberer = Numberer::new(&workspaces, &outputs, &config, &Pinned::default());
+
+        assert_eq!(numberer.nums.values().copied().collect::<Vec<_>>(), [11, 23, 21, 22]);
+
+        let mut pinned = Pinned::default();
+        pinned.set(4, true, &workspaces);
+        let numberer = Numberer::new(&workspaces, &outputs, &Config::default(), &pinned);
+
+        assert_eq!(numberer.nums.values().copied().collect::<Vec<_>>(), [11, 21, 23, 22]);
+    }
+
+    #[test]
+    fn sequential_numbers_not_held_stay_apart() {
+        let outputs = [output("DP-1", 0, true, true), output("DP-2", 1920, true, true)];
+        let workspaces = [workspace(1, 1, "DP-1"), workspace(2, 2, "DP-1"), workspace(3, 3, "DP-2")];
+        let config = Config {
+            numbering: Numbering::Sequential,
+            ..Config::default()
+        };
+        let numberer = Numberer::new(&workspaces, &outputs, &config, &Pinned::default());
+
+        assert_eq!([11, 12, 21].map(|num| numberer.external(num)), [1, 2, 3]);
+        assert!(numberer.external(13) > 3);
+        assert!(numberer.external(22) > numberer.external(13));
+        assert!(numberer.external(32) > numberer.external(22));
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use swayipc::Error;
+
+use crate::state;
+
+const FILE: &str = "order";
+
+/// The order `sworker sort --save` remembered, for `sworker sort --by saved` to bring back.
+///
+/// Workspaces are tracked by their con id, so the order outlives renaming and moves between outputs.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct Order {
+    workspaces: Vec<i64>,
+}
+
+impl Order {
+    pub(crate) fn load() -> Result<Self, Error> {
+        state::load(FILE)
+    }
+
+    pub(crate) fn store(&self) -> Result<(), Error> {
+        state::store(FILE, self)
+    }
+
+    pub(crate) const fn new(workspaces: Vec<i64>) -> Self {
+        Self { workspaces }
+    }
+
+    /// The place of `id` in the order, none if it was not remembered.
+    pub(crate) fn rank(&self, id: i64) -> Option<usize> {
+        self.workspaces.iter().position(|workspace| *workspace == id)
+    }
+}
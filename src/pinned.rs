@@ -0,0 +1,45 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use swayipc::{Error, Workspace};
+
+use crate::state;
+
+const FILE: &str = "pinned";
+
+/// The workspaces that keep their position when others are inserted, moved or closed.
+///
+/// Like the history, workspaces are tracked by their con id, which survives renaming. Only `sworker pin` and
+/// `sworker unpin` write this, the daemon just reads it whenever it reorders.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct Pinned {
+    workspaces: BTreeSet<i64>,
+}
+
+impl Pinned {
+    pub(crate) fn load() -> Result<Self, Error> {
+        state::load(FILE)
+    }
+
+    pub(crate) fn store(&self) -> Result<(), Error> {
+        state::store(FILE, self)
+    }
+
+    pub(crate) fn contains(&self, id: i64) -> bool {
+        self.workspaces.contains(&id)
+    }
+
+    /// Pin the workspace `id`, or unpin it unless `pinned`.
+    ///
+    /// Workspaces sway closed in the meantime are forgotten along the way.
+    pub(crate) fn set(&mut self, id: i64, pinned: bool, workspaces: &[Workspace]) {
+        self.workspaces
+            .retain(|pinned| workspaces.iter().any(|workspace| workspace.id == *pinned));
+
+        if pinned {
+            self.workspaces.insert(id);
+        } else {
+            self.workspaces.remove(&id);
+        }
+    }
+}
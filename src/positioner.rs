@@ -10,6 +10,8 @@ pub(crate) struct Positioner {
     pub(crate) group_highest: i32,
     pub(crate) position: i32,
     pub(crate) position_highest: i32,
+    /// The positions of the current group that hold a workspace, in order. Gaps are missing.
+    positions: Vec<i32>,
 }
 
 impl Positioner {
@@ -18,16 +20,24 @@ impl Positioner {
     pub(crate) fn new(workspaces: &[Workspace]) -> Self {
         let focused = workspaces.iter().find(|ws| ws.focused).map_or(NUMBERS_PER_GROUP, |ws| ws.num);
 
+        Self::at(workspaces, focused)
+    }
+
+    /// Create a new Positioner as if the workspace `focused` was focused, whether it exists or not.
+    pub(crate) fn at(workspaces: &[Workspace], focused: i32) -> Self {
+        let mut positions = workspaces
+            .iter()
+            .filter(|ws| ws.num / NUMBERS_PER_GROUP == focused / NUMBERS_PER_GROUP)
+            .map(|ws| ws.num % NUMBERS_PER_GROUP)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+
         Self {
             group: focused / NUMBERS_PER_GROUP,
             group_highest: workspaces.iter().map(|ws| ws.num / NUMBERS_PER_GROUP).max().unwrap_or(1),
             position: focused % NUMBERS_PER_GROUP,
-            position_highest: workspaces
-                .iter()
-                .filter(|ws| ws.num / NUMBERS_PER_GROUP == focused / NUMBERS_PER_GROUP)
-                .map(|ws| ws.num % NUMBERS_PER_GROUP)
-                .max()
-                .unwrap_or(0),
+            position_highest: positions.last().copied().unwrap_or(0),
+            positions: positions.into_iter().filter(|position| *position > 0).collect(),
         }
     }
 
@@ -35,17 +45,13 @@ impl Positioner {
         self.group * NUMBERS_PER_GROUP + self.position
     }
 
-    /// Move `add` groups, wrapping at the first and last group.
-    pub(crate) const fn wrapping_group(&self, add: i32) -> i32 {
-        let group = self.group + add;
+    /// Move `add` groups, wrapping at the first and last group as often as `add` requires.
+    ///
+    /// `add` is reduced to less than one round first, so no offset overflows.
+    pub(crate) fn wrapping_group(&self, add: i32) -> i32 {
+        let last = self.group_last();
 
-        if group > self.group_highest {
-            1
-        } else if group < 1 {
-            self.group_highest
-        } else {
-            group
-        }
+        (self.group - 1 + add.rem_euclid(last)).rem_euclid(last) + 1
     }
 
     /// The number of `group` at the current position, clamped to the existing groups.
@@ -53,21 +59,25 @@ impl Positioner {
         group.clamp(1, self.group_highest) * NUMBERS_PER_GROUP + self.position
     }
 
-    pub(crate) const fn wrapping_position_add(&self, add: i32) -> i32 {
-        self.wrapping_position_to(self.position + add)
-    }
-
-    /// The number of `position` in the current group, wrapping at the first and last position.
-    pub(crate) const fn wrapping_position_to(&self, position: i32) -> i32 {
-        let position = if position > self.position_highest {
-            1
-        } else if position < 1 {
-            self.position_highest
-        } else {
-            position
+    /// The number `add` positions away, wrapping at the first and last position as often as `add` requires.
+    ///
+    /// Gaps the config keeps, or pinned workspaces leave, are skipped rather than counted.
+    pub(crate) fn wrapping_position_add(&self, add: i32) -> i32 {
+        let Some(index) = self.positions.iter().position(|position| *position == self.position) else {
+            return self.wrapping_position_to(self.position + add.rem_euclid(self.position_last()));
         };
 
-        self.group * NUMBERS_PER_GROUP + position
+        // A group holds no more than ten positions, so these never truncate.
+        let len = i32::try_from(self.positions.len()).unwrap_or(i32::MAX);
+        let index = (i32::try_from(index).unwrap_or(0) + add.rem_euclid(len)).rem_euclid(len);
+
+        self.group * NUMBERS_PER_GROUP + self.positions[usize::try_from(index).unwrap_or(0)]
+    }
+
+    /// The number of `position` in the current group, wrapping at the first and last position as often as
+    /// `position` requires.
+    pub(crate) fn wrapping_position_to(&self, position: i32) -> i32 {
+        self.group * NUMBERS_PER_GROUP + (position - 1).rem_euclid(self.position_last()) + 1
     }
 
     /// The number of `position` in the current group, clamped to the positions a group can hold.
@@ -77,7 +87,17 @@ impl Positioner {
 
     /// The number of `position` in the current group, clamped to the positions the group holds.
     pub(crate) fn saturating_position_to(&self, position: i32) -> i32 {
-        self.group * NUMBERS_PER_GROUP + position.clamp(1, self.position_highest.max(1))
+        self.group * NUMBERS_PER_GROUP + position.clamp(1, self.position_last())
+    }
+
+    /// The last group.
+    pub(crate) fn group_last(&self) -> i32 {
+        self.group_highest.max(1)
+    }
+
+    /// The last position of the current group.
+    pub(crate) fn position_last(&self) -> i32 {
+        self.position_highest.max(1)
     }
 
     pub(crate) const fn is_start(&self) -> bool {
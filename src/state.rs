@@ -0,0 +1,58 @@
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use swayipc::Error;
+
+/// The file `name` is stored in, below `$XDG_RUNTIME_DIR/sworker/<socket>`, if there is a socket and a runtime dir.
+///
+/// Sway counts its con ids from 1 again when it restarts, so the state of one sway instance must never be read by
+/// the next. Every instance has its own socket, named after its pid, which keeps their states apart.
+fn path(name: &str) -> Option<PathBuf> {
+    // The socket `Connection` connects to, preferred in the same order.
+    let socket = env::var_os("I3SOCK").or_else(|| env::var_os("SWAYSOCK"));
+    let instance = socket.as_deref().map(Path::new).and_then(Path::file_name)?;
+
+    // A shared directory like `/tmp` would let other users plant state, so there is none without a runtime dir.
+    let runtime = env::var_os("XDG_RUNTIME_DIR")?;
+
+    Some(PathBuf::from(runtime)
+        .join(env!("CARGO_PKG_NAME"))
+        .join(instance)
+        .join(name)
+        .with_extension("json"))
+}
+
+/// Load the state stored as `name`, or its default if nothing was stored yet or there is nowhere to store it.
+pub(crate) fn load<T: DeserializeOwned + Default>(name: &str) -> Result<T, Error> {
+    let Some(path) = path(name) else {
+        return Ok(T::default());
+    };
+
+    match fs::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Store `state` as `name`, or nothing if there is nowhere to store it.
+///
+/// The file is replaced as a whole, so a command reading it at the same time never sees half of it.
+pub(crate) fn store<T: Serialize>(name: &str, state: &T) -> Result<(), Error> {
+    let Some(path) = path(name) else {
+        return Ok(());
+    };
+    let temp = path.with_extension("tmp");
+
+    if let Some(dir) = path.parent() {
+        fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    }
+
+    fs::write(&temp, serde_json::to_vec(state)?)?;
+    fs::rename(temp, path)?;
+
+    Ok(())
+}